@@ -1,3 +1,5 @@
 cargo contract build --manifest-path rmrk/Cargo.toml --release
 cargo contract build --manifest-path rmrk_catalog/Cargo.toml --release
-cargo contract build --manifest-path rmrk_proxy/Cargo.toml --release
\ No newline at end of file
+cargo contract build --manifest-path psp22_example/Cargo.toml --release
+cargo contract build --manifest-path rmrk_proxy/Cargo.toml --release
+cargo contract build --manifest-path rmrk_proxy_migration/Cargo.toml --release
\ No newline at end of file
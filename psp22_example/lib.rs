@@ -0,0 +1,73 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod psp22_example {
+    use openbrush::{
+        contracts::psp22::*,
+        traits::Storage,
+    };
+
+    // Psp22Contract contract storage
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct Psp22Contract {
+        #[storage_field]
+        psp22: psp22::Data,
+    }
+
+    impl PSP22 for Psp22Contract {}
+
+    impl Psp22Contract {
+        /// Instantiate new Psp22Contract contract minting `total_supply` to the caller
+        #[ink(constructor)]
+        pub fn new(total_supply: Balance) -> Self {
+            let mut instance = Psp22Contract::default();
+            instance
+                ._mint_to(Self::env().caller(), total_supply)
+                .expect("Should mint total supply");
+            instance
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink::env::test;
+
+        const TOTAL_SUPPLY: Balance = 1_000;
+
+        #[ink::test]
+        fn new_mints_total_supply_to_caller() {
+            let token = Psp22Contract::new(TOTAL_SUPPLY);
+            let accounts = default_accounts();
+            assert_eq!(token.total_supply(), TOTAL_SUPPLY);
+            assert_eq!(token.balance_of(accounts.alice), TOTAL_SUPPLY);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_from_needs_allowance() {
+            let mut token = Psp22Contract::new(TOTAL_SUPPLY);
+            let accounts = default_accounts();
+            test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.bob, 100, vec![]),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(token.approve(accounts.bob, 100).is_ok());
+            test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(token
+                .transfer_from(accounts.alice, accounts.bob, 100, vec![])
+                .is_ok());
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+    }
+}
@@ -1,6 +1,19 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![feature(min_specialization)]
 
+use openbrush::contracts::psp34::Id;
+use rmrk::errors::Error;
+
+pub mod royalty;
+
+/// Lazy minting which lets the caller know the Id of a minted token.
+#[openbrush::trait_definition]
+pub trait MintingLazyWithId {
+    /// Lazy mints a token to the caller like `MintingLazy::mint` and returns its Id.
+    #[ink(message, payable)]
+    fn mint_with_id(&mut self) -> Result<Id, Error>;
+}
+
 #[openbrush::contract]
 pub mod rmrk_equippable_lazy {
     use ink::{
@@ -19,14 +32,26 @@ pub mod rmrk_equippable_lazy {
             },
             reentrancy_guard::*,
         },
+        modifiers,
         traits::{
             Storage,
             String,
         },
     };
 
+    use crate::{
+        royalty::{
+            self,
+            royalty_amount,
+            Royalty,
+            RoyaltyError,
+            MAX_ROYALTY,
+        },
+        MintingLazyWithId,
+    };
     use rmrk::{
         config,
+        errors::Error,
         extensions::*,
         query::*,
         storage::*,
@@ -190,6 +215,24 @@ pub mod rmrk_equippable_lazy {
         parent: AccountId,
     }
 
+    /// Event emitted when the default royalty is set or removed.
+    #[ink(event)]
+    pub struct DefaultRoyaltySet {
+        #[ink(topic)]
+        receiver: Option<AccountId>,
+        royalty: u16,
+    }
+
+    /// Event emitted when a royalty of a token is set or reset to the default one.
+    #[ink(event)]
+    pub struct TokenRoyaltySet {
+        #[ink(topic)]
+        token: Id,
+        #[ink(topic)]
+        receiver: Option<AccountId>,
+        royalty: u16,
+    }
+
     // Rmrk contract storage
     #[ink(storage)]
     #[derive(Default, Storage)]
@@ -212,6 +255,8 @@ pub mod rmrk_equippable_lazy {
         minting_autoindex: MintingAutoIndexData,
         #[storage_field]
         equippable: EquippableData,
+        #[storage_field]
+        royalty: royalty::Data,
     }
 
     impl PSP34 for Rmrk {}
@@ -232,6 +277,97 @@ pub mod rmrk_equippable_lazy {
 
     impl Query for Rmrk {}
 
+    impl Royalty for Rmrk {
+        #[ink(message)]
+        fn royalty_info(&self, id: Id, sale_price: Balance) -> (AccountId, Balance) {
+            match self
+                .royalty
+                .token_royalty
+                .get(&id)
+                .or(self.royalty.default_royalty)
+            {
+                Some((receiver, royalty)) => (receiver, royalty_amount(sale_price, royalty)),
+                None => (AccountId::from([0x0; 32]), 0),
+            }
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn set_default_royalty(
+            &mut self,
+            receiver: AccountId,
+            royalty: u16,
+        ) -> Result<(), RoyaltyError> {
+            if royalty > MAX_ROYALTY {
+                return Err(RoyaltyError::InvalidRoyalty)
+            }
+            self.royalty.default_royalty = Some((receiver, royalty));
+            self.env().emit_event(DefaultRoyaltySet {
+                receiver: Some(receiver),
+                royalty,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn delete_default_royalty(&mut self) -> Result<(), RoyaltyError> {
+            self.royalty.default_royalty = None;
+            self.env().emit_event(DefaultRoyaltySet {
+                receiver: None,
+                royalty: 0,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn set_token_royalty(
+            &mut self,
+            id: Id,
+            receiver: AccountId,
+            royalty: u16,
+        ) -> Result<(), RoyaltyError> {
+            if royalty > MAX_ROYALTY {
+                return Err(RoyaltyError::InvalidRoyalty)
+            }
+            if PSP34::owner_of(self, id.clone()).is_none() {
+                return Err(RoyaltyError::TokenNotExists)
+            }
+            self.royalty.token_royalty.insert(&id, &(receiver, royalty));
+            self.env().emit_event(TokenRoyaltySet {
+                token: id,
+                receiver: Some(receiver),
+                royalty,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn reset_token_royalty(&mut self, id: Id) -> Result<(), RoyaltyError> {
+            if PSP34::owner_of(self, id.clone()).is_none() {
+                return Err(RoyaltyError::TokenNotExists)
+            }
+            self.royalty.token_royalty.remove(&id);
+            self.env().emit_event(TokenRoyaltySet {
+                token: id,
+                receiver: None,
+                royalty: 0,
+            });
+            Ok(())
+        }
+    }
+
+    impl MintingLazyWithId for Rmrk {
+        #[ink(message, payable)]
+        fn mint_with_id(&mut self) -> Result<Id, Error> {
+            MintingLazy::mint(self)?;
+            // Lazy mints take the next auto index, so the latest one is the minted token.
+            Ok(Id::U64(self.minting_autoindex.last_token_id))
+        }
+    }
+
     impl Rmrk {
         /// Instantiate new RMRK contract
         #[allow(clippy::too_many_arguments)]
@@ -243,10 +379,14 @@ pub mod rmrk_equippable_lazy {
             max_supply: Option<u64>,
             price_per_mint: Balance,
             collection_metadata: String,
-            _royalty_receiver: AccountId,
-            _royalty: u8,
-        ) -> Self {
+            royalty_receiver: AccountId,
+            royalty: u16,
+        ) -> Result<Self, RoyaltyError> {
+            if royalty > MAX_ROYALTY {
+                return Err(RoyaltyError::InvalidRoyalty)
+            }
             let mut instance = Rmrk::default();
+            instance.royalty.default_royalty = Some((royalty_receiver, royalty));
             config::with_admin(&mut instance, Self::env().caller());
             config::with_lazy_mint(&mut instance, price_per_mint);
             config::with_collection(
@@ -257,7 +397,7 @@ pub mod rmrk_equippable_lazy {
                 collection_metadata,
                 max_supply,
             );
-            instance
+            Ok(instance)
         }
     }
 
@@ -432,4 +572,128 @@ pub mod rmrk_equippable_lazy {
             });
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test;
+
+        const PRICE: Balance = 1_000;
+
+        #[ink::test]
+        fn mint_with_id_returns_minted_id() {
+            let mut contract = init();
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            assert_eq!(contract.mint_with_id(), Ok(Id::U64(1)));
+            assert_eq!(contract.mint_with_id(), Ok(Id::U64(2)));
+            assert!(
+                PSP34::transfer(&mut contract, default_accounts().bob, Id::U64(1), vec![]).is_ok()
+            );
+            assert_eq!(contract.mint_with_id(), Ok(Id::U64(3)));
+            assert_eq!(
+                PSP34::owner_of(&contract, Id::U64(3)),
+                Some(default_accounts().alice)
+            );
+        }
+
+        #[ink::test]
+        fn constructor_sets_default_royalty() {
+            let contract = init();
+            assert_eq!(
+                contract.royalty_info(Id::U64(1), 1_050),
+                (default_accounts().bob, 105)
+            );
+        }
+
+        #[ink::test]
+        fn constructor_fails_if_royalty_too_high() {
+            let result = Rmrk::new(
+                String::from("Test"),
+                String::from("TST"),
+                String::from("ipfs://base"),
+                None,
+                PRICE,
+                String::from("ipfs://collection"),
+                default_accounts().bob,
+                MAX_ROYALTY + 1,
+            );
+            assert_eq!(result.err(), Some(RoyaltyError::InvalidRoyalty));
+        }
+
+        #[ink::test]
+        fn set_default_royalty_works() {
+            let mut contract = init();
+            let charlie = default_accounts().charlie;
+            assert!(contract.set_default_royalty(charlie, 500).is_ok());
+            assert_eq!(contract.royalty_info(Id::U64(1), 1_000), (charlie, 50));
+            assert_eq!(
+                contract.set_default_royalty(charlie, MAX_ROYALTY + 1),
+                Err(RoyaltyError::InvalidRoyalty)
+            );
+            assert!(contract.delete_default_royalty().is_ok());
+            assert_eq!(
+                contract.royalty_info(Id::U64(1), 1_000),
+                (AccountId::from([0x0; 32]), 0)
+            );
+        }
+
+        #[ink::test]
+        fn set_token_royalty_works() {
+            let mut contract = init();
+            let accounts = default_accounts();
+            assert_eq!(
+                contract.set_token_royalty(Id::U64(1), accounts.charlie, 2_000),
+                Err(RoyaltyError::TokenNotExists)
+            );
+            assert_eq!(
+                contract.reset_token_royalty(Id::U64(1)),
+                Err(RoyaltyError::TokenNotExists)
+            );
+            test::set_value_transferred::<ink::env::DefaultEnvironment>(PRICE);
+            let token_id = contract.mint_with_id().unwrap();
+
+            assert!(contract
+                .set_token_royalty(token_id.clone(), accounts.charlie, 2_000)
+                .is_ok());
+            assert_eq!(
+                contract.royalty_info(token_id.clone(), 1_000),
+                (accounts.charlie, 200)
+            );
+            assert!(contract.reset_token_royalty(token_id.clone()).is_ok());
+            assert_eq!(contract.royalty_info(token_id, 1_000), (accounts.bob, 100));
+        }
+
+        #[ink::test]
+        fn set_royalty_fails_if_not_admin() {
+            let mut contract = init();
+            let accounts = default_accounts();
+            test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.set_default_royalty(accounts.bob, 5_000),
+                Err(RoyaltyError::AccessControl(AccessControlError::MissingRole))
+            );
+            assert_eq!(
+                contract.reset_token_royalty(Id::U64(1)),
+                Err(RoyaltyError::AccessControl(AccessControlError::MissingRole))
+            );
+        }
+
+        fn init() -> Rmrk {
+            Rmrk::new(
+                String::from("Test"),
+                String::from("TST"),
+                String::from("ipfs://base"),
+                None,
+                PRICE,
+                String::from("ipfs://collection"),
+                default_accounts().bob,
+                1_000,
+            )
+            .unwrap()
+        }
+
+        fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+    }
 }
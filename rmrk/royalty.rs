@@ -0,0 +1,85 @@
+//! Royalty info for marketplaces, following ERC-2981 semantics.
+//!
+//! A collection has a default royalty which can be overridden for single tokens.
+//! Royalties are expressed in basis points of a sale price.
+
+use openbrush::{
+    contracts::{
+        access_control::AccessControlError,
+        psp34::Id,
+    },
+    storage::Mapping,
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+
+pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+/// Maximum royalty, in basis points.
+pub const MAX_ROYALTY: u16 = 10_000;
+
+#[derive(Default, Debug)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct Data {
+    pub default_royalty: Option<(AccountId, u16)>, // Collection royalty receiver and basis points
+    pub token_royalty: Mapping<Id, (AccountId, u16)>, // Royalty overrides per token
+}
+
+#[openbrush::trait_definition]
+pub trait Royalty {
+    /// Gets a receiver of the royalty for token `id` and the royalty amount for `sale_price`.
+    /// Returns the zero account and zero amount if no royalty is set.
+    #[ink(message)]
+    fn royalty_info(&self, id: Id, sale_price: Balance) -> (AccountId, Balance);
+
+    /// Sets a royalty for all tokens without their own royalty.
+    #[ink(message)]
+    fn set_default_royalty(
+        &mut self,
+        receiver: AccountId,
+        royalty: u16,
+    ) -> Result<(), RoyaltyError>;
+
+    /// Removes the royalty for all tokens without their own royalty.
+    #[ink(message)]
+    fn delete_default_royalty(&mut self) -> Result<(), RoyaltyError>;
+
+    /// Sets a royalty for token `id`, overriding the default one.
+    #[ink(message)]
+    fn set_token_royalty(
+        &mut self,
+        id: Id,
+        receiver: AccountId,
+        royalty: u16,
+    ) -> Result<(), RoyaltyError>;
+
+    /// Removes the royalty override of token `id`, so the default royalty applies again.
+    /// Fails if the token doesn't exist.
+    #[ink(message)]
+    fn reset_token_royalty(&mut self, id: Id) -> Result<(), RoyaltyError>;
+}
+
+/// Computes the royalty amount of `royalty` basis points for `sale_price`.
+pub fn royalty_amount(sale_price: Balance, royalty: u16) -> Balance {
+    sale_price / MAX_ROYALTY as Balance * royalty as Balance
+        + sale_price % MAX_ROYALTY as Balance * royalty as Balance / MAX_ROYALTY as Balance
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RoyaltyError {
+    /// A caller is not allowed to change royalties.
+    AccessControl(AccessControlError),
+    /// A royalty is higher than `MAX_ROYALTY`.
+    InvalidRoyalty,
+    /// A token with given id doesn't exist.
+    TokenNotExists,
+}
+
+impl From<AccessControlError> for RoyaltyError {
+    fn from(error: AccessControlError) -> Self {
+        Self::AccessControl(error)
+    }
+}
@@ -0,0 +1,272 @@
+//! Typed calls to the RMRK, catalog, PSP22 payment token and randomness oracle contracts.
+//!
+//! Selectors of RMRK, catalog and PSP22 messages are checked against their trait definitions when
+//! compiling tests, so a renamed trait or message doesn't go unnoticed.
+
+use crate::ProxyError;
+use ink::{
+    env::{
+        call::{
+            build_call,
+            ExecutionInput,
+            Selector,
+        },
+        DefaultEnvironment,
+        Result as EnvResult,
+    },
+    prelude::vec::Vec,
+    MessageResult,
+};
+use openbrush::{
+    contracts::{
+        psp22::PSP22Error,
+        psp34::{
+            Id,
+            PSP34Error,
+        },
+    },
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+use rmrk::{
+    errors::Error as RmrkError,
+    types::AssetId,
+};
+
+pub const TOTAL_ASSETS_SELECTOR: [u8; 4] = ink::selector_bytes!("MultiAsset::total_assets");
+pub const MINT_SELECTOR: [u8; 4] = ink::selector_bytes!("MintingLazyWithId::mint_with_id");
+pub const PRICE_SELECTOR: [u8; 4] = ink::selector_bytes!("MintingLazy::price");
+pub const ADD_ASSET_TO_TOKEN_SELECTOR: [u8; 4] =
+    ink::selector_bytes!("MultiAsset::add_asset_to_token");
+pub const TRANSFER_SELECTOR: [u8; 4] = ink::selector_bytes!("PSP34::transfer");
+pub const TOTAL_SUPPLY_SELECTOR: [u8; 4] = ink::selector_bytes!("PSP34::total_supply");
+pub const PARTS_COUNT_SELECTOR: [u8; 4] = ink::selector_bytes!("Catalog::get_parts_count");
+pub const PSP22_TRANSFER_SELECTOR: [u8; 4] = ink::selector_bytes!("PSP22::transfer");
+pub const PSP22_TRANSFER_FROM_SELECTOR: [u8; 4] = ink::selector_bytes!("PSP22::transfer_from");
+
+/// Gets a number of asset entries defined on the RMRK contract.
+pub fn total_assets(rmrk_contract: AccountId) -> Result<u32, ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(rmrk_contract)
+        .exec_input(ExecutionInput::new(Selector::new(TOTAL_ASSETS_SELECTOR)))
+        .returns::<u32>()
+        .try_invoke();
+    query_result(result).map_err(|_| ProxyError::TotalAssetsQueryFailed)
+}
+
+/// Lazy mints a token to the proxy paying `value` and returns its Id.
+pub fn mint(rmrk_contract: AccountId, value: Balance) -> Result<Id, ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(rmrk_contract)
+        .transferred_value(value)
+        .exec_input(ExecutionInput::new(Selector::new(MINT_SELECTOR)))
+        .returns::<Result<Id, RmrkError>>()
+        .try_invoke();
+    call_result(result, ProxyError::RmrkError)
+}
+
+/// Gets a lazy mint price of the RMRK contract.
+pub fn price(rmrk_contract: AccountId) -> Result<Balance, ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(rmrk_contract)
+        .exec_input(ExecutionInput::new(Selector::new(PRICE_SELECTOR)))
+        .returns::<Balance>()
+        .try_invoke();
+    query_result(result)
+}
+
+/// Adds an asset entry to a token owned by the proxy.
+pub fn add_asset_to_token(
+    rmrk_contract: AccountId,
+    token_id: Id,
+    asset_id: AssetId,
+) -> Result<(), ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(rmrk_contract)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ADD_ASSET_TO_TOKEN_SELECTOR))
+                .push_arg(token_id)
+                .push_arg(asset_id)
+                .push_arg(None::<AssetId>),
+        )
+        .returns::<Result<(), RmrkError>>()
+        .try_invoke();
+    call_result(result, ProxyError::AddTokenAssetError)
+}
+
+/// Transfers a token owned by the proxy to `to`.
+pub fn transfer(rmrk_contract: AccountId, to: AccountId, token_id: Id) -> Result<(), ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(rmrk_contract)
+        .exec_input(
+            ExecutionInput::new(Selector::new(TRANSFER_SELECTOR))
+                .push_arg(to)
+                .push_arg(token_id)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<Result<(), PSP34Error>>()
+        .try_invoke();
+    call_result(result, ProxyError::OwnershipTransferError)
+}
+
+/// Gets a number of tokens minted on the RMRK contract.
+pub fn total_supply(rmrk_contract: AccountId) -> Result<Balance, ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(rmrk_contract)
+        .exec_input(ExecutionInput::new(Selector::new(TOTAL_SUPPLY_SELECTOR)))
+        .returns::<Balance>()
+        .try_invoke();
+    query_result(result)
+}
+
+/// Gets a number of parts defined on the catalog contract.
+pub fn parts_count(catalog_contract: AccountId) -> Result<u32, ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(catalog_contract)
+        .exec_input(ExecutionInput::new(Selector::new(PARTS_COUNT_SELECTOR)))
+        .returns::<u32>()
+        .try_invoke();
+    query_result(result)
+}
+
+/// Transfers `value` of PSP22 `token` owned by the proxy to `to`.
+pub fn psp22_transfer(token: AccountId, to: AccountId, value: Balance) -> Result<(), ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(token)
+        .exec_input(
+            ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                .push_arg(to)
+                .push_arg(value)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<Result<(), PSP22Error>>()
+        .try_invoke();
+    call_result(result, ProxyError::TokenTransferFailed)
+}
+
+/// Transfers `value` of PSP22 `token` from `from` to `to` out of the allowance `from` gave the proxy.
+pub fn psp22_transfer_from(
+    token: AccountId,
+    from: AccountId,
+    to: AccountId,
+    value: Balance,
+) -> Result<(), ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(token)
+        .exec_input(
+            ExecutionInput::new(Selector::new(PSP22_TRANSFER_FROM_SELECTOR))
+                .push_arg(from)
+                .push_arg(to)
+                .push_arg(value)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<Result<(), PSP22Error>>()
+        .try_invoke();
+    call_result(result, ProxyError::TokenTransferFailed)
+}
+
+/// Asks a randomness oracle for a seed. The oracle message takes `subject` and returns `[u8; 32]`.
+pub fn random_seed(
+    oracle_contract: AccountId,
+    selector: [u8; 4],
+    subject: &[u8],
+) -> Result<[u8; 32], ProxyError> {
+    let result = build_call::<DefaultEnvironment>()
+        .call(oracle_contract)
+        .exec_input(ExecutionInput::new(Selector::new(selector)).push_arg(subject))
+        .returns::<[u8; 32]>()
+        .try_invoke();
+    query_result(result).map_err(|_| ProxyError::RandomnessUnavailable)
+}
+
+/// Flattens a result of a cross-contract call, mapping an error returned by the callee
+/// with `map_error`.
+pub fn call_result<T, E>(
+    result: EnvResult<MessageResult<Result<T, E>>>,
+    map_error: impl FnOnce(E) -> ProxyError,
+) -> Result<T, ProxyError> {
+    query_result(result)?.map_err(map_error)
+}
+
+/// Unwraps a result of a cross-contract call to a message which doesn't return `Result`.
+pub fn query_result<T>(result: EnvResult<MessageResult<T>>) -> Result<T, ProxyError> {
+    match result {
+        // Handle environment errors.
+        EnvResult::Err(_) => Err(ProxyError::EnvironmentError),
+        // Handle language errors.
+        EnvResult::Ok(MessageResult::Err(_)) => Err(ProxyError::LanguageError),
+        EnvResult::Ok(MessageResult::Ok(value)) => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openbrush::contracts::{
+        psp22::psp22_external::PSP22,
+        psp34::psp34_external::PSP34,
+    };
+    use rmrk::{
+        storage::catalog_external::Catalog,
+        traits::{
+            mintinglazy_external::MintingLazy,
+            multiasset_external::MultiAsset,
+        },
+    };
+    use rmrk_equippable_lazy::mintinglazywithid_external::MintingLazyWithId;
+
+    /// Fails to compile if `$selector` is not the selector of `$message` of `$trait`.
+    macro_rules! assert_selector {
+        ($selector:expr, $trait:path, $message:literal) => {
+            const _: () = assert!(
+                u32::from_be_bytes($selector)
+                    == u32::from_be_bytes(
+                        <<ink::reflect::TraitDefinitionRegistry<DefaultEnvironment> as $trait>::__ink_TraitInfo
+                            as ink::reflect::TraitMessageInfo<{ ink::selector_id!($message) }>>::SELECTOR
+                    )
+            );
+        };
+    }
+
+    assert_selector!(TOTAL_ASSETS_SELECTOR, MultiAsset, "total_assets");
+    assert_selector!(MINT_SELECTOR, MintingLazyWithId, "mint_with_id");
+    assert_selector!(PRICE_SELECTOR, MintingLazy, "price");
+    assert_selector!(
+        ADD_ASSET_TO_TOKEN_SELECTOR,
+        MultiAsset,
+        "add_asset_to_token"
+    );
+    assert_selector!(TRANSFER_SELECTOR, PSP34, "transfer");
+    assert_selector!(TOTAL_SUPPLY_SELECTOR, PSP34, "total_supply");
+    assert_selector!(PARTS_COUNT_SELECTOR, Catalog, "get_parts_count");
+    assert_selector!(PSP22_TRANSFER_SELECTOR, PSP22, "transfer");
+    assert_selector!(PSP22_TRANSFER_FROM_SELECTOR, PSP22, "transfer_from");
+
+    #[ink::test]
+    fn call_result_preserves_callee_errors() {
+        let failed: EnvResult<MessageResult<Result<(), PSP34Error>>> =
+            Ok(Ok(Err(PSP34Error::TokenNotExists)));
+        assert_eq!(
+            call_result(failed, ProxyError::OwnershipTransferError),
+            Err(ProxyError::OwnershipTransferError(
+                PSP34Error::TokenNotExists
+            ))
+        );
+        let succeeded: EnvResult<MessageResult<Result<u32, PSP34Error>>> = Ok(Ok(Ok(7)));
+        assert_eq!(
+            call_result(succeeded, ProxyError::OwnershipTransferError),
+            Ok(7)
+        );
+    }
+
+    #[ink::test]
+    fn query_result_maps_call_errors() {
+        let trapped: EnvResult<MessageResult<u32>> = Err(ink::env::Error::CalleeTrapped);
+        assert_eq!(query_result(trapped), Err(ProxyError::EnvironmentError));
+        let lang_error: EnvResult<MessageResult<u32>> = Ok(Err(ink::LangError::CouldNotReadInput));
+        assert_eq!(query_result(lang_error), Err(ProxyError::LanguageError));
+        assert_eq!(query_result::<u32>(Ok(Ok(7))), Ok(7));
+    }
+}
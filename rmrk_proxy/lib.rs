@@ -1,7 +1,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![feature(min_specialization)]
 
+pub mod client;
+pub mod merkle;
+pub mod pricing;
 pub mod proxy;
+pub mod randomness;
 pub mod types;
 
 pub use proxy::*;
@@ -0,0 +1,80 @@
+//! Merkle proof verification used for allowlisted minting.
+//!
+//! Leaves are `keccak256(SCALE(account, quota))` and inner nodes hash the sorted pair of their
+//! children, so proofs don't need to carry left/right position flags.
+
+use ink::{
+    env::hash::{
+        HashOutput,
+        Keccak256,
+    },
+    primitives::{
+        AccountId,
+        Hash,
+    },
+};
+
+/// Computes an allowlist leaf for `account` allowed to mint up to `quota` tokens.
+pub fn allowlist_leaf(account: &AccountId, quota: u32) -> Hash {
+    let mut output = <Keccak256 as HashOutput>::Type::default();
+    ink::env::hash_encoded::<Keccak256, _>(&(account, quota), &mut output);
+    Hash::from(output)
+}
+
+/// Checks if `leaf` is a part of the tree with given `root`.
+pub fn verify(root: &Hash, leaf: Hash, proof: &[Hash]) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed_root == *root
+}
+
+fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut input = [0u8; 64];
+    input[..32].copy_from_slice(left.as_ref());
+    input[32..].copy_from_slice(right.as_ref());
+
+    let mut output = <Keccak256 as HashOutput>::Type::default();
+    ink::env::hash_bytes::<Keccak256>(&input, &mut output);
+    Hash::from(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn verify_works() {
+        let leaves = [
+            allowlist_leaf(&AccountId::from([0x01; 32]), 1),
+            allowlist_leaf(&AccountId::from([0x02; 32]), 2),
+            allowlist_leaf(&AccountId::from([0x03; 32]), 3),
+        ];
+        let node = hash_pair(&leaves[0], &leaves[1]);
+        let root = hash_pair(&node, &leaves[2]);
+
+        assert!(verify(&root, leaves[0], &[leaves[1], leaves[2]]));
+        assert!(verify(&root, leaves[1], &[leaves[0], leaves[2]]));
+        assert!(verify(&root, leaves[2], &[node]));
+    }
+
+    #[ink::test]
+    fn verify_fails_for_wrong_leaf() {
+        let alice = allowlist_leaf(&AccountId::from([0x01; 32]), 1);
+        let bob = allowlist_leaf(&AccountId::from([0x02; 32]), 1);
+        let root = hash_pair(&alice, &bob);
+
+        assert!(!verify(
+            &root,
+            allowlist_leaf(&AccountId::from([0x01; 32]), 5),
+            &[bob]
+        ));
+        assert!(!verify(
+            &root,
+            allowlist_leaf(&AccountId::from([0x03; 32]), 1),
+            &[bob]
+        ));
+        assert!(!verify(&root, alice, &[]));
+    }
+}
@@ -0,0 +1,135 @@
+//! Bonding curve math used to price tokens by the number of tokens minted through the proxy.
+//!
+//! Exponential growth is computed with a fixed point factor of 9 decimals. A factor which
+//! doesn't fit into `u128` can only mean a price far above any sane cap, so it yields the cap.
+
+use crate::{
+    BondingCurve,
+    Curve,
+    BASIS_POINTS,
+};
+use openbrush::traits::Balance;
+
+/// Fixed point one of the exponential growth factor.
+const ONE: u128 = 1_000_000_000;
+
+/// Gets a price of the next token once `minted` tokens are minted.
+pub fn price_after(curve: &BondingCurve, minted: u32) -> Balance {
+    let price = match curve.curve {
+        Curve::Linear { slope } => {
+            curve
+                .base_price
+                .saturating_add(slope.saturating_mul(minted as Balance))
+        }
+        Curve::Exponential { growth } => {
+            growth_factor(growth, minted)
+                .and_then(|factor| curve.base_price.checked_mul(factor))
+                .map(|price| price / ONE)
+                .unwrap_or(Balance::MAX)
+        }
+    };
+    price.min(curve.cap)
+}
+
+/// Gets a total price of `count` tokens minted one after another once `minted` tokens are minted.
+/// Returns `None` if the total overflows.
+pub fn batch_cost(curve: &BondingCurve, minted: u32, count: u32) -> Option<Balance> {
+    (0..count).try_fold(0 as Balance, |total, index| {
+        total.checked_add(price_after(curve, minted.saturating_add(index)))
+    })
+}
+
+/// Computes `(1 + growth / BASIS_POINTS) ^ exponent` as a fixed point number.
+fn growth_factor(growth: u16, exponent: u32) -> Option<u128> {
+    let mut factor = ONE + ONE / BASIS_POINTS as u128 * growth as u128;
+    let mut result = ONE;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, factor)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            factor = mul(factor, factor)?;
+        }
+    }
+    Some(result)
+}
+
+fn mul(a: u128, b: u128) -> Option<u128> {
+    a.checked_mul(b).map(|product| product / ONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linear(slope: Balance, cap: Balance) -> BondingCurve {
+        BondingCurve {
+            base_price: 100,
+            curve: Curve::Linear { slope },
+            cap,
+        }
+    }
+
+    fn exponential(growth: u16, cap: Balance) -> BondingCurve {
+        BondingCurve {
+            base_price: 1_000_000,
+            curve: Curve::Exponential { growth },
+            cap,
+        }
+    }
+
+    #[ink::test]
+    fn linear_price_rises_up_to_cap() {
+        let curve = linear(10, 150);
+        assert_eq!(price_after(&curve, 0), 100);
+        assert_eq!(price_after(&curve, 1), 110);
+        assert_eq!(price_after(&curve, 5), 150);
+        assert_eq!(price_after(&curve, 6), 150);
+        assert_eq!(price_after(&curve, u32::MAX), 150);
+        assert_eq!(
+            price_after(&linear(Balance::MAX, Balance::MAX), 2),
+            Balance::MAX
+        );
+    }
+
+    #[ink::test]
+    fn exponential_price_compounds_up_to_cap() {
+        // 1% growth per token.
+        let curve = exponential(100, 1_100_000);
+        assert_eq!(price_after(&curve, 0), 1_000_000);
+        assert_eq!(price_after(&curve, 1), 1_010_000);
+        assert_eq!(price_after(&curve, 2), 1_020_100);
+        assert_eq!(price_after(&curve, 9), 1_093_685);
+        assert_eq!(price_after(&curve, 10), 1_100_000);
+        assert_eq!(price_after(&curve, u32::MAX), 1_100_000);
+        assert_eq!(
+            price_after(&exponential(0, Balance::MAX), u32::MAX),
+            1_000_000
+        );
+    }
+
+    #[ink::test]
+    fn exponential_price_overflow_yields_cap() {
+        let curve = exponential(u16::MAX, Balance::MAX);
+        assert_eq!(price_after(&curve, u32::MAX), Balance::MAX);
+    }
+
+    #[ink::test]
+    fn batch_cost_sums_prices_across_cap() {
+        let curve = linear(10, 150);
+        assert_eq!(batch_cost(&curve, 0, 0), Some(0));
+        assert_eq!(batch_cost(&curve, 0, 1), Some(100));
+        // 130 + 140 + 150 + 150
+        assert_eq!(batch_cost(&curve, 3, 4), Some(570));
+        assert_eq!(batch_cost(&curve, u32::MAX, 2), Some(300));
+        assert_eq!(batch_cost(&linear(0, Balance::MAX), 0, 2), Some(200));
+        let expensive = BondingCurve {
+            base_price: Balance::MAX,
+            curve: Curve::Linear { slope: 0 },
+            cap: Balance::MAX,
+        };
+        assert_eq!(batch_cost(&expensive, 0, 2), None);
+    }
+}
@@ -19,45 +19,76 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-#[ink::contract]
-mod rmrk_proxy {
+#[openbrush::contract]
+pub mod rmrk_proxy {
     use crate::{
+        client,
         ensure,
+        merkle,
+        pricing,
+        randomness::{
+            CommitRevealRandom,
+            OracleRandom,
+            PseudoRandom,
+            RandomnessSource,
+        },
+        AllowlistProof,
+        AuctionState,
+        CommitRevealConfig,
+        ConfigChange,
+        ConfigKey,
+        DataV0,
+        DutchAuction,
+        PricingMode,
+        Proposal,
         ProxyError,
+        RandomnessConfig,
+        Reservation,
+        SalePhase,
+        SaleStatus,
+        SaleTerms,
+        ADMIN,
+        BASIS_POINTS,
+        CONFIG_MANAGER,
+        DEFAULT_EXPIRY_FEE,
+        DEFAULT_MAX_PER_TX,
+        PAUSER,
+        PRESALE_PHASE,
+        PRICE_MANAGER,
+        PROBABILITY_PRECISION,
+        RESERVE_MINTER,
+        STORAGE_VERSION,
     };
     use ink::{
-        env::{
-            call::{
-                build_call,
-                ExecutionInput,
-                Selector,
-            },
-            hash,
-            DefaultEnvironment,
-            Result as EnvResult,
+        env::hash,
+        prelude::{
+            boxed::Box,
+            vec::Vec,
         },
-        prelude::vec::Vec,
-        MessageResult,
     };
     use openbrush::{
         contracts::{
-            ownable::*,
+            access_control::*,
+            pausable::*,
             psp34::Id,
             reentrancy_guard::*,
         },
         modifiers,
         traits::Storage,
     };
+    use rmrk::types::AssetId;
 
     // Proxy contract storage
     #[ink(storage)]
     #[derive(Default, Storage)]
     pub struct RmrkProxy {
         #[storage_field]
-        ownable: ownable::Data,
+        access: access_control::Data,
         #[storage_field]
         guard: reentrancy_guard::Data,
         #[storage_field]
+        pause: pausable::Data,
+        #[storage_field]
         proxy: crate::types::Data,
     }
 
@@ -66,131 +97,517 @@ mod rmrk_proxy {
     pub struct TokenMinted {
         #[ink(topic)]
         id: Id,
-        #[ink(topic)]
         price: Option<Balance>,
+        #[ink(topic)]
+        recipient: AccountId,
+        asset_id: AssetId,
+    }
+
+    /// Event emitted when minting is paused.
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when minting is unpaused.
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when the minting price is changed.
+    #[ink(event)]
+    pub struct MintPriceChanged {
+        price: Balance,
+    }
+
+    /// Event emitted when the RMRK contract address is changed.
+    #[ink(event)]
+    pub struct RmrkContractChanged {
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Event emitted when the catalog contract address is changed.
+    #[ink(event)]
+    pub struct CatalogContractChanged {
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Event emitted when the contract code is replaced.
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        code_hash: [u8; 32],
+    }
+
+    /// Event emitted when storage is migrated to a newer layout.
+    #[ink(event)]
+    pub struct Migrated {
+        from: u32,
+        to: u32,
+    }
+
+    /// Event emitted when a Dutch auction ends.
+    #[ink(event)]
+    pub struct AuctionEnded {
+        #[ink(topic)]
+        auction_id: u32,
+        clearing_price: Balance,
+    }
+
+    /// Event emitted when a timelocked config change is proposed.
+    #[ink(event)]
+    pub struct ChangeProposed {
+        change: ConfigChange,
+        executable_at: Timestamp,
+    }
+
+    /// Event emitted when a pending config change is cancelled.
+    #[ink(event)]
+    pub struct ProposalCancelled {
+        key: ConfigKey,
+    }
+
+    /// Event emitted when an admin starts handing the admin role over.
+    #[ink(event)]
+    pub struct AdminTransferStarted {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    /// Event emitted when the new admin accepts the admin role.
+    #[ink(event)]
+    pub struct AdminTransferred {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
     }
 
+    impl AccessControl for RmrkProxy {}
+
     impl RmrkProxy {
         #[ink(constructor)]
         pub fn new(
             rmrk_contract: AccountId,
             catalog_contract: AccountId,
             mint_price: Balance,
+        ) -> Self {
+            Self::new_with_randomness(
+                rmrk_contract,
+                catalog_contract,
+                mint_price,
+                RandomnessConfig::PseudoRandom,
+            )
+        }
+
+        /// Creates a proxy which picks assets using given source of randomness.
+        #[ink(constructor)]
+        pub fn new_with_randomness(
+            rmrk_contract: AccountId,
+            catalog_contract: AccountId,
+            mint_price: Balance,
+            randomness: RandomnessConfig,
         ) -> Self {
             let mut instance = Self::default();
             instance.proxy.rmrk_contract = Option::Some(rmrk_contract);
             instance.proxy.catalog_contract = Option::Some(catalog_contract);
             instance.proxy.salt = 0;
             instance.proxy.mint_price = mint_price;
+            instance.proxy.randomness = randomness;
+            instance.init(Self::env().caller());
+            instance
+        }
 
-            let caller = instance.env().caller();
-            instance._init_with_owner(caller);
+        /// Converts storage written by code which predates storage versioning into layout 1.
+        /// `owner` gets every role and all proceeds, as the deployer of a new proxy does.
+        /// Used by the `rmrk_proxy_migration` contract, which stores the result.
+        pub fn from_v0(owner: AccountId, data: &DataV0) -> Self {
+            let mut instance = Self::default();
+            instance.proxy.rmrk_contract = data.rmrk_contract;
+            instance.proxy.catalog_contract = data.catalog_contract;
+            instance.proxy.salt = data.salt;
+            instance.proxy.mint_price = data.mint_price;
+            instance.init(owner);
             instance
         }
 
         /// Mints a token on proxied RMRK contract.
+        ///
+        /// While presale is active, caller has to provide a proof of being allowlisted.
+        #[ink(message, payable)]
+        #[modifiers(when_not_paused, non_reentrant)]
+        pub fn mint(&mut self, allowlist: Option<AllowlistProof>) -> Result<Id, ProxyError> {
+            let mut token_ids = self.mint_tokens(Self::env().caller(), 1, allowlist)?;
+            Ok(token_ids.remove(0))
+        }
+
+        /// Mints `count` tokens on proxied RMRK contract in a single call.
+        ///
+        /// Transferred value must be `count` times the active sale price. Each token gets its own random asset.
+        #[ink(message, payable)]
+        #[modifiers(when_not_paused, non_reentrant)]
+        pub fn mint_many(
+            &mut self,
+            count: u32,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<Vec<Id>, ProxyError> {
+            self.mint_tokens(Self::env().caller(), count, allowlist)
+        }
+
+        /// Mints a token paid by the caller to `recipient`, e.g. as a gift or from a checkout relayer.
+        ///
+        /// Allowlist and per-wallet limits apply to the recipient, so `allowlist` has to prove
+        /// that the recipient is allowlisted.
+        #[ink(message, payable)]
+        #[modifiers(when_not_paused, non_reentrant)]
+        pub fn mint_to(
+            &mut self,
+            recipient: AccountId,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<Id, ProxyError> {
+            let mut token_ids = self.mint_tokens(recipient, 1, allowlist)?;
+            Ok(token_ids.remove(0))
+        }
+
+        /// Mints `count` tokens paid by the caller to `recipient`. See `mint_to` and `mint_many`.
+        #[ink(message, payable)]
+        #[modifiers(when_not_paused, non_reentrant)]
+        pub fn mint_many_to(
+            &mut self,
+            recipient: AccountId,
+            count: u32,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<Vec<Id>, ProxyError> {
+            self.mint_tokens(recipient, count, allowlist)
+        }
+
+        /// Mints free tokens from the reserve allocation, `count` tokens to each
+        /// `(recipient, count, asset_id)`.
+        ///
+        /// Tokens get `asset_id` if given, otherwise a random asset. Nobody pays for reserved
+        /// tokens, so the RMRK contract's lazy mint price has to be zero.
+        #[ink(message)]
+        #[modifiers(only_role(RESERVE_MINTER), when_not_paused, non_reentrant)]
+        pub fn reserve_mint(
+            &mut self,
+            recipients: Vec<(AccountId, u32, Option<AssetId>)>,
+        ) -> Result<Vec<Id>, ProxyError> {
+            let rmrk_contract = self.rmrk_contract()?;
+            let count = recipients
+                .iter()
+                .try_fold(0u32, |total, (_, count, _)| total.checked_add(*count))
+                .ok_or(ProxyError::ReserveExceeded)?;
+            ensure!(count > 0, ProxyError::BadMintCount);
+            ensure!(count <= self.proxy.reserve, ProxyError::ReserveExceeded);
+            self.proxy.reserve -= count;
+            self.proxy.total_minted = self.proxy.total_minted.saturating_add(count);
+
+            let total_assets = self.total_assets(rmrk_contract)?;
+            let mut randomness = self.randomness_source();
+            let mut token_ids = Vec::with_capacity(count as usize);
+            for (recipient, recipient_count, asset_id) in recipients {
+                for _ in 0..recipient_count {
+                    let asset_id = match asset_id {
+                        Some(asset_id) => self.take_asset(asset_id, total_assets)?,
+                        None => self.pick_asset(total_assets, randomness.as_mut())?,
+                    };
+                    let token_id =
+                        self.mint_token(rmrk_contract, asset_id, Payment::Reserve, recipient)?;
+                    token_ids.push(token_id);
+                }
+            }
+
+            Ok(token_ids)
+        }
+
+        /// Reserves a token while commit-reveal is enabled. The caller pays the sale price and
+        /// commits `keccak256(SCALE((secret, caller)))`. The token is minted and its asset picked
+        /// only when the secret is revealed with `reveal_mint`. The lazy mint price of the RMRK
+        /// contract is locked in at commit time.
+        ///
+        /// Returns an id of the reservation.
         #[ink(message, payable)]
+        #[modifiers(when_not_paused, non_reentrant)]
+        pub fn commit_mint(
+            &mut self,
+            commitment: Hash,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<u64, ProxyError> {
+            ensure!(
+                self.proxy.commit_reveal.is_some(),
+                ProxyError::CommitRevealDisabled
+            );
+            ensure!(
+                self.payment_token().is_none(),
+                ProxyError::NativePaymentRequired
+            );
+            let rmrk_contract = self.rmrk_contract()?;
+            let reservation_id = self.commit_reservation(commitment, allowlist)?;
+            // Cross-contract query goes last, after the local checks.
+            let rmrk_price = client::price(rmrk_contract)?;
+            self.lock_rmrk_price(reservation_id, rmrk_price)?;
+            Ok(reservation_id)
+        }
+
+        /// Reveals the secret of a reservation, mints the token with an asset picked from
+        /// the secret and the entropy pool as of block `committed_at + reveal_delay`, and
+        /// transfers it to the caller. The RMRK contract is paid the price locked in at commit
+        /// time, so a reveal fails if that price has changed since. The reservation can then be
+        /// refunded once it expires.
+        ///
+        /// Possible only after `reveal_delay` blocks and before the reservation expires, also
+        /// while minting is paused.
+        #[ink(message)]
         #[modifiers(non_reentrant)]
-        pub fn mint(&mut self) -> Result<Id, ProxyError> {
-            const MAX_ASSETS: u32 = 255;
-            let rmrk_contract = self.proxy.rmrk_contract.unwrap();
+        pub fn reveal_mint(&mut self, reservation_id: u64, secret: Hash) -> Result<Id, ProxyError> {
+            let rmrk_contract = self.rmrk_contract()?;
+            let config = self
+                .proxy
+                .commit_reveal
+                .clone()
+                .ok_or(ProxyError::CommitRevealDisabled)?;
+            let reservation = self.owned_reservation(reservation_id)?;
 
-            let transferred_value = Self::env().transferred_value();
+            let block_number = Self::env().block_number();
+            let reveal_from = reservation.committed_at.saturating_add(config.reveal_delay);
+            ensure!(block_number >= reveal_from, ProxyError::RevealTooEarly);
+            ensure!(
+                block_number < reveal_from.saturating_add(config.reveal_window),
+                ProxyError::ReservationExpired
+            );
             ensure!(
-                transferred_value == self.proxy.mint_price,
-                ProxyError::BadMintValue
+                commitment_hash(&secret, &reservation.owner) == reservation.commitment,
+                ProxyError::InvalidSecret
             );
+            self.proxy.reservations.remove(&reservation_id);
+            let rmrk_price = self.proxy.reservation_rmrk_prices.get(&reservation_id);
+            self.proxy.reservation_rmrk_prices.remove(&reservation_id);
 
-            let total_assets = build_call::<DefaultEnvironment>()
-                .call(rmrk_contract)
-                .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!(
-                    "MultiAsset::total_assets"
-                ))))
-                .returns::<u32>()
-                .try_invoke()
-                .unwrap()
-                .unwrap();
-            ensure!(total_assets > 0, ProxyError::NoAssetsDefined);
-            // TODO This is temporary since current pseudo random generator is not working with big numbers.
-            ensure!(total_assets <= MAX_ASSETS, ProxyError::TooManyAssetsDefined);
-
-            let mint_result = build_call::<DefaultEnvironment>()
-                .call(rmrk_contract)
-                .transferred_value(transferred_value)
-                .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!(
-                    "MintingLazy::mint"
-                ))))
-                .returns::<core::result::Result<(), rmrk::errors::Error>>()
-                .try_invoke();
-
-            match mint_result {
-                // Handle environment errors.
-                EnvResult::Err(_) => Err(ProxyError::EnvironmentError),
-                // Handle language errors.
-                EnvResult::Ok(MessageResult::Err(_)) => Err(ProxyError::LanguageError),
-                // Handle contract errors.
-                EnvResult::Ok(MessageResult::Ok(Result::<(), rmrk::errors::Error>::Err(_))) => {
-                    Err(ProxyError::RmrkError(
-                        mint_result.unwrap().unwrap().unwrap_err(),
-                    ))
+            let mut randomness =
+                self.reveal_randomness(reservation_id, &reservation, secret, reveal_from)?;
+            let total_assets = self.total_assets(rmrk_contract)?;
+            let asset_id = self.pick_asset(total_assets, &mut randomness)?;
+            // Reservations made before prices were locked pay the current price.
+            let payment = match rmrk_price {
+                Some(rmrk_price) => {
+                    Payment::Committed {
+                        price: reservation.paid,
+                        rmrk_price,
+                    }
                 }
-                _ => Ok(()),
-            }?;
-
-            // TODO make RMRK MintingLazy to return minted token Id.
-            let token_id = build_call::<DefaultEnvironment>()
-                .call(rmrk_contract)
-                .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!(
-                    "PSP34::total_supply"
-                ))))
-                .returns::<u64>()
-                .try_invoke()
-                .unwrap()
-                .unwrap();
+                None => {
+                    Payment::Native {
+                        price: reservation.paid,
+                        held: 0,
+                    }
+                }
+            };
+            self.mint_token(rmrk_contract, asset_id, payment, reservation.owner)
+        }
 
-            let asset_id = self.get_pseudo_random((total_assets - 1) as u8) + 1;
-            let add_asset_result = build_call::<DefaultEnvironment>()
-                .call(self.proxy.rmrk_contract.unwrap())
-                .exec_input(
-                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
-                        "MultiAsset::add_asset_to_token"
-                    )))
-                    .push_arg(Id::U64(token_id)) // TODO check if there is other way to determine token Id, beside reading totalSupply?
-                    .push_arg(asset_id as u32)
-                    .push_arg(None::<u32>),
-                )
-                .returns::<()>()
-                .try_invoke()
-                .map_err(|_| ProxyError::AddTokenAssetError)?;
-            add_asset_result.map_err(|_| ProxyError::AddTokenAssetError)?;
+        /// Refunds a reservation which has not been revealed before it expired. The outcome of
+        /// an expired reservation is public, so `expiry_fee` basis points of the payment are kept
+        /// and split among payees. Reservations are refunded in full once commit-reveal is
+        /// disabled.
+        ///
+        /// Returns the amount refunded.
+        #[ink(message)]
+        #[modifiers(non_reentrant)]
+        pub fn refund_reservation(&mut self, reservation_id: u64) -> Result<Balance, ProxyError> {
+            let reservation = self.owned_reservation(reservation_id)?;
+            let mut fee = 0;
+            if let Some(config) = &self.proxy.commit_reveal {
+                let expires_at = reservation
+                    .committed_at
+                    .saturating_add(config.reveal_delay)
+                    .saturating_add(config.reveal_window);
+                ensure!(
+                    Self::env().block_number() >= expires_at,
+                    ProxyError::ReservationNotExpired
+                );
+                fee = share_of(reservation.paid, self.expiry_fee());
+            }
+            self.proxy.reservations.remove(&reservation_id);
+            self.proxy.reservation_rmrk_prices.remove(&reservation_id);
+            self.release_allowance(reservation.owner, reservation.phase);
+            self.proxy.total_minted = self.proxy.total_minted.saturating_sub(1);
+            if fee > 0 {
+                self.split_payment(None, fee);
+            }
+
+            let refund = reservation.paid - fee;
+            Self::env()
+                .transfer(reservation.owner, refund)
+                .map_err(|_| ProxyError::TransferFailed)?;
+            Ok(refund)
+        }
+
+        /// Sends all pending mint proceeds of `payee` to it. Anyone can trigger the payment.
+        ///
+        /// Returns the amount paid.
+        #[ink(message)]
+        #[modifiers(non_reentrant)]
+        pub fn withdraw(&mut self, payee: AccountId) -> Result<Balance, ProxyError> {
+            let amount = self.pending_payment(payee);
+            ensure!(amount > 0, ProxyError::NothingToWithdraw);
+            self.proxy.pending_payments.remove(&payee);
+
+            Self::env()
+                .transfer(payee, amount)
+                .map_err(|_| ProxyError::TransferFailed)?;
+            Ok(amount)
+        }
+
+        /// Sends all pending mint proceeds of `payee` in PSP22 `token` to it. Anyone can trigger
+        /// the payment.
+        ///
+        /// Returns the amount paid.
+        #[ink(message)]
+        #[modifiers(non_reentrant)]
+        pub fn withdraw_token(
+            &mut self,
+            token: AccountId,
+            payee: AccountId,
+        ) -> Result<Balance, ProxyError> {
+            let amount = self.pending_token_payment(token, payee);
+            ensure!(amount > 0, ProxyError::NothingToWithdraw);
+            self.proxy.pending_token_payments.remove(&(token, payee));
+
+            client::psp22_transfer(token, payee, amount)?;
+            Ok(amount)
+        }
+
+        /// Pauses minting. Refunds and withdrawals stay available while paused.
+        #[ink(message)]
+        #[modifiers(only_role(PAUSER))]
+        pub fn pause(&mut self) -> Result<(), ProxyError> {
+            self._pause::<ProxyError>()?;
+            Self::env().emit_event(Paused {
+                account: Self::env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Unpauses minting. Only an admin can unpause, so pausers can't undo each other's stop.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn unpause(&mut self) -> Result<(), ProxyError> {
+            self._unpause::<ProxyError>()?;
+            Self::env().emit_event(Unpaused {
+                account: Self::env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Replaces the contract code with code uploaded under `code_hash`, keeping storage.
+        /// Call `migrate` afterwards if the new code uses a newer storage layout. Possible only
+        /// while the timelock delay is zero, otherwise see `propose_upgrade`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn upgrade(&mut self, code_hash: [u8; 32]) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::Upgrade(code_hash))
+        }
+
+        /// Moves data stored by older code into the current storage layout.
+        ///
+        /// Returns the storage version migrated from.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn migrate(&mut self) -> Result<u32, ProxyError> {
+            let from = self.storage_version();
+            ensure!(from < STORAGE_VERSION, ProxyError::NothingToMigrate);
+
+            // Layout 0 can't be decoded by this code and goes through `rmrk_proxy_migration`
+            // instead. Steps converting data of later layouts go here, oldest first,
+            // e.g. `if from < 2 { ... }`.
+            self.proxy.storage_version.set(&STORAGE_VERSION);
 
+            Self::env().emit_event(Migrated {
+                from,
+                to: STORAGE_VERSION,
+            });
+            Ok(from)
+        }
+
+        /// Starts handing the admin role of the caller over to `new_admin`. The role moves
+        /// only once `new_admin` calls `accept_ownership`. Replaces a pending transfer.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn transfer_ownership(&mut self, new_admin: AccountId) -> Result<(), ProxyError> {
             let caller = Self::env().caller();
-            let transfer_token_result = build_call::<DefaultEnvironment>()
-                .call(rmrk_contract)
-                .exec_input(
-                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP34::transfer")))
-                        .push_arg(caller)
-                        .push_arg(Id::U64(token_id))
-                        .push_arg(Vec::<u8>::new()),
-                )
-                .returns::<()>()
-                .try_invoke()
-                .map_err(|_| ProxyError::OwnershipTransferError)?;
-            transfer_token_result.map_err(|_| ProxyError::OwnershipTransferError)?;
+            self.proxy.admin_transfer.set(&Some((caller, new_admin)));
+            Self::env().emit_event(AdminTransferStarted {
+                from: caller,
+                to: new_admin,
+            });
+            Ok(())
+        }
+
+        /// Accepts the admin role offered with `transfer_ownership`. The other roles of the
+        /// previous admin move along, so the previous admin keeps none of them.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<(), ProxyError> {
+            let caller = Self::env().caller();
+            let (from, to) = self
+                .proxy
+                .admin_transfer
+                .get()
+                .flatten()
+                .ok_or(ProxyError::NotPendingAdmin)?;
+            ensure!(caller == to, ProxyError::NotPendingAdmin);
+            // The offer lapses if its maker has lost the role in the meantime.
+            ensure!(self.has_role(ADMIN, from), AccessControlError::MissingRole);
+
+            self.proxy.admin_transfer.set(&None);
+            for role in [ADMIN, PRICE_MANAGER, CONFIG_MANAGER, PAUSER, RESERVE_MINTER] {
+                if self.has_role(role, from) {
+                    self._do_revoke_role(role, from);
+                    self._setup_role(role, to);
+                }
+            }
+            Self::env().emit_event(AdminTransferred { from, to });
+            Ok(())
+        }
+
+        /// Gets a version of the stored layout. It lags behind `STORAGE_VERSION` after
+        /// an upgrade until `migrate` is called, and is zero if it predates versioning.
+        #[ink(message)]
+        pub fn storage_version(&self) -> u32 {
+            self.proxy.storage_version.get().unwrap_or(0)
+        }
 
-            Ok(Id::U64(token_id))
+        /// Gets a hash of the code the contract currently runs.
+        #[ink(message)]
+        pub fn code_hash(&self) -> Result<Hash, ProxyError> {
+            Self::env()
+                .own_code_hash()
+                .map_err(|_| ProxyError::EnvironmentError)
+        }
+
+        /// Checks if minting is paused.
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self._paused()
         }
 
         /// Gets a RMRK contract address.
         #[ink(message)]
-        pub fn rmrk_contract_address(&self) -> AccountId {
-            self.proxy.rmrk_contract.unwrap()
+        pub fn rmrk_contract_address(&self) -> Option<AccountId> {
+            self.proxy.rmrk_contract
         }
 
         /// Gets a catalog contract address.
         #[ink(message)]
-        pub fn catalog_contract_address(&self) -> AccountId {
-            self.proxy.catalog_contract.unwrap()
+        pub fn catalog_contract_address(&self) -> Option<AccountId> {
+            self.proxy.catalog_contract
         }
 
         /// Gets a minting price.
@@ -199,122 +616,3790 @@ mod rmrk_proxy {
             self.proxy.mint_price
         }
 
-        /// Sets a RMRK contract address.
+        /// Gets a presale minting price. Falls back to `mint_price` if not set.
+        #[ink(message)]
+        pub fn presale_price(&self) -> Option<Balance> {
+            self.proxy.presale_price
+        }
+
+        /// Gets a Merkle root of allowlisted accounts. Presale is active while the root is set.
+        #[ink(message)]
+        pub fn allowlist_root(&self) -> Option<Hash> {
+            self.proxy.allowlist_root
+        }
+
+        /// Gets a number of tokens minted by `account` in the given sale phase.
+        /// Without phases, `PRESALE_PHASE` refers to the presale and `None` to the public sale.
+        #[ink(message)]
+        pub fn phase_minted(&self, phase: Option<u32>, account: AccountId) -> u32 {
+            self.proxy.phase_minted.get(&(phase, account)).unwrap_or(0)
+        }
+
+        /// Gets a total number of tokens minted by `account` through the proxy.
+        #[ink(message)]
+        pub fn minted_by(&self, account: AccountId) -> u32 {
+            self.proxy.minted.get(&account).unwrap_or(0)
+        }
+
+        /// Gets commit-reveal settings. Tokens are minted directly while not set.
+        #[ink(message)]
+        pub fn commit_reveal(&self) -> Option<CommitRevealConfig> {
+            self.proxy.commit_reveal.clone()
+        }
+
+        /// Gets the share of the payment, in basis points, kept when an expired reservation
+        /// is refunded.
+        #[ink(message)]
+        pub fn expiry_fee(&self) -> u16 {
+            self.proxy.expiry_fee.get().unwrap_or(DEFAULT_EXPIRY_FEE)
+        }
+
+        /// Gets the source of randomness used for direct mints.
+        #[ink(message)]
+        pub fn randomness(&self) -> RandomnessConfig {
+            self.proxy.randomness.clone()
+        }
+
+        /// Gets a pending reservation made with `commit_mint`.
+        #[ink(message)]
+        pub fn reservation(&self, reservation_id: u64) -> Option<Reservation> {
+            self.proxy.reservations.get(&reservation_id)
+        }
+
+        /// Gets all scheduled sale phases.
+        #[ink(message)]
+        pub fn phases(&self) -> Vec<SalePhase> {
+            self.proxy.phases.clone()
+        }
+
+        /// Gets an index of the sale phase active at the current block timestamp.
+        #[ink(message)]
+        pub fn active_phase(&self) -> Option<u32> {
+            let now = self.env().block_timestamp();
+            self.proxy
+                .phases
+                .iter()
+                .position(|phase| phase.start <= now && now < phase.end)
+                .map(|index| index as u32)
+        }
+
+        /// Gets a sale status at the current block timestamp.
+        #[ink(message)]
+        pub fn sale_status(&self) -> SaleStatus {
+            let now = self.env().block_timestamp();
+            match (self.proxy.phases.last(), self.sale_terms()) {
+                (_, Ok(terms)) if terms.allowlist_root.is_some() => SaleStatus::Presale,
+                (_, Ok(_)) => SaleStatus::Public,
+                (Some(last), Err(_)) if last.end <= now => SaleStatus::Ended,
+                (None, Err(_)) if self.auction_ended() => SaleStatus::Ended,
+                _ => SaleStatus::Closed,
+            }
+        }
+
+        /// Gets the rarity table as `(asset_id, weight)` pairs. Empty table means all assets are equally likely.
+        #[ink(message)]
+        pub fn asset_weights(&self) -> Vec<(AssetId, u32)> {
+            self.proxy.asset_weights.clone()
+        }
+
+        /// Gets a probability of each asset being assigned to a minted token,
+        /// in parts per million. Assets with no remaining supply are left out.
+        #[ink(message)]
+        pub fn asset_probabilities(&self) -> Result<Vec<(AssetId, u32)>, ProxyError> {
+            let weights = if self.proxy.asset_weights.is_empty() {
+                let rmrk_contract = self.rmrk_contract()?;
+                let total_assets = self.total_assets(rmrk_contract)?;
+                (1..=total_assets).map(|id| (id, 1)).collect()
+            } else {
+                self.proxy.asset_weights.clone()
+            };
+            let available = available_weights(&weights, &self.proxy.exhausted_assets);
+            ensure!(!available.is_empty(), ProxyError::SoldOut);
+            Ok(probabilities(&available))
+        }
+
+        /// Gets a remaining supply of an asset. `None` means the asset supply is unlimited.
+        #[ink(message)]
+        pub fn asset_supply(&self, asset_id: AssetId) -> Option<u32> {
+            self.proxy.asset_supply.get(&asset_id)
+        }
+
+        /// Gets payees of mint proceeds with their shares in basis points.
+        #[ink(message)]
+        pub fn payees(&self) -> Vec<(AccountId, u16)> {
+            self.proxy.payees.clone()
+        }
+
+        /// Gets mint proceeds which `account` can withdraw.
+        #[ink(message)]
+        pub fn pending_payment(&self, account: AccountId) -> Balance {
+            self.proxy.pending_payments.get(&account).unwrap_or(0)
+        }
+
+        /// Gets mint proceeds in PSP22 `token` which `account` can withdraw.
+        #[ink(message)]
+        pub fn pending_token_payment(&self, token: AccountId, account: AccountId) -> Balance {
+            self.proxy
+                .pending_token_payments
+                .get(&(token, account))
+                .unwrap_or(0)
+        }
+
+        /// Gets a PSP22 token mints are paid in. Mints are paid in the native token if not set.
+        #[ink(message)]
+        pub fn payment_token(&self) -> Option<AccountId> {
+            self.proxy.payment_token.get().flatten()
+        }
+
+        /// Gets a maximum number of tokens which can be minted through the proxy, reserve included.
+        #[ink(message)]
+        pub fn max_supply(&self) -> Option<u32> {
+            self.proxy.max_supply
+        }
+
+        /// Gets a number of tokens minted or reserved with `commit_mint` through the proxy.
+        #[ink(message)]
+        pub fn total_minted(&self) -> u32 {
+            self.proxy.total_minted
+        }
+
+        /// Gets a number of tokens left for `reserve_mint`. Public minting can't use them.
+        #[ink(message)]
+        pub fn reserve_remaining(&self) -> u32 {
+            self.proxy.reserve
+        }
+
+        /// Gets a maximum number of tokens which can be minted in a single call.
+        #[ink(message)]
+        pub fn max_per_tx(&self) -> u32 {
+            self.proxy.max_per_tx
+        }
+
+        /// Gets a maximum number of tokens a single account can mint in a sale phase.
+        #[ink(message)]
+        pub fn max_per_wallet(&self) -> Option<u32> {
+            self.proxy.max_per_wallet
+        }
+
+        /// Gets how the price of the public sale without phases is computed.
+        #[ink(message)]
+        pub fn pricing_mode(&self) -> PricingMode {
+            self.proxy.pricing.get().unwrap_or_default()
+        }
+
+        /// Gets a price of a token in the sale active at the current block timestamp.
         #[ink(message)]
-        #[modifiers(only_owner)]
+        pub fn current_price(&self) -> Result<Balance, ProxyError> {
+            self.sale_terms().map(|terms| terms.price)
+        }
+
+        /// Gets a total price of the next `count` tokens in the sale active at the current
+        /// block timestamp. On a bonding curve every token is priced by its own position.
+        #[ink(message)]
+        pub fn quote_price(&self, count: u32) -> Result<Balance, ProxyError> {
+            let terms = self.sale_terms()?;
+            self.batch_cost(&terms, count)
+                .ok_or(ProxyError::BadMintValue)
+        }
+
+        /// Gets an id of the latest Dutch auction.
+        #[ink(message)]
+        pub fn auction_id(&self) -> u32 {
+            self.proxy.auction_id.get().unwrap_or(0)
+        }
+
+        /// Gets sales of the latest Dutch auction.
+        #[ink(message)]
+        pub fn auction_state(&self) -> AuctionState {
+            self.proxy.auction.get().unwrap_or_default()
+        }
+
+        /// Gets a clearing price of an ended Dutch auction.
+        #[ink(message)]
+        pub fn clearing_price(&self, auction_id: u32) -> Option<Balance> {
+            self.proxy.clearing_prices.get(&auction_id)
+        }
+
+        /// Gets a delay of timelocked config changes, in milliseconds.
+        #[ink(message)]
+        pub fn timelock_delay(&self) -> Timestamp {
+            self.proxy.timelock_delay.get().unwrap_or(0)
+        }
+
+        /// Gets pending timelocked config changes.
+        #[ink(message)]
+        pub fn proposals(&self) -> Vec<Proposal> {
+            self.proxy.proposals.get().unwrap_or_default()
+        }
+
+        /// Gets an account which can accept the admin role with `accept_ownership`.
+        #[ink(message)]
+        pub fn pending_admin(&self) -> Option<AccountId> {
+            self.proxy.admin_transfer.get().flatten().map(|(_, to)| to)
+        }
+
+        /// Sets a RMRK contract address. Possible only while the timelock delay is zero,
+        /// otherwise see `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
         pub fn set_rmrk_contract_address(
             &mut self,
             new_contract_address: AccountId,
         ) -> Result<(), ProxyError> {
-            self.proxy.rmrk_contract = Option::Some(new_contract_address);
-            Ok(())
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::RmrkContract(new_contract_address))
+        }
+
+        /// Sets a catalog contract address. Possible only while the timelock delay is zero,
+        /// otherwise see `propose_catalog_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_catalog_contract_address(
+            &mut self,
+            new_contract_address: AccountId,
+        ) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::CatalogContract(new_contract_address))
+        }
+
+        /// Sets a minting price. Possible only while the timelock delay is zero,
+        /// otherwise see `propose_mint_price`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn set_mint_price(&mut self, new_mint_price: Balance) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::MintPrice(new_mint_price))
+        }
+
+        /// Sets how the price of the public sale without phases is computed. Setting a Dutch
+        /// auction starts a new auction. A running auction with sales has to end first.
+        /// Possible only while the timelock delay is zero, otherwise see `propose_pricing_mode`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn set_pricing_mode(&mut self, mode: PricingMode) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::PricingMode(mode))
+        }
+
+        /// Sets a PSP22 token mints are paid in, or switches back to the native token with `None`.
+        /// Mint and phase prices are in units of the payment token, and the RMRK lazy mint price
+        /// has to be zero. Commit-reveal and auction rebates refund in the native token, so a token
+        /// can't be set while either is enabled. Possible only while the timelock delay is zero,
+        /// otherwise see `propose_payment_token`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn set_payment_token(&mut self, token: Option<AccountId>) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::PaymentToken(token))
+        }
+
+        /// Ends the running Dutch auction. The clearing price is the price of the last sale,
+        /// or the current price if nothing was sold. With rebates, payees get the clearing
+        /// price of every sold token and buyers can claim the rest with `claim_rebate`.
+        ///
+        /// A price manager can end the auction at any time. Anyone else can end it once its
+        /// price has dropped to the floor or the public supply is sold out.
+        ///
+        /// Returns the clearing price.
+        #[ink(message)]
+        pub fn end_auction(&mut self) -> Result<Balance, ProxyError> {
+            let auction = match self.pricing_mode() {
+                PricingMode::DutchAuction(auction) => auction,
+                _ => return Err(ProxyError::AuctionNotRunning),
+            };
+            let auction_id = self.auction_id();
+            ensure!(
+                !self.proxy.clearing_prices.contains(&auction_id),
+                ProxyError::AuctionNotRunning
+            );
+            let now = Self::env().block_timestamp();
+            let at_floor = now >= auction.start && auction.price_at(now) == auction.floor_price;
+            let sold_out = !valid_supply(
+                self.proxy.max_supply,
+                self.proxy.total_minted.saturating_add(1),
+                self.proxy.reserve,
+            );
+            ensure!(
+                at_floor || sold_out || self.has_role(PRICE_MANAGER, Self::env().caller()),
+                AccessControlError::MissingRole
+            );
+
+            let state = self.auction_state();
+            let clearing_price = if state.sold > 0 {
+                state.last_price
+            } else {
+                auction.price_at(now)
+            };
+            self.proxy
+                .clearing_prices
+                .insert(&auction_id, &clearing_price);
+            if auction.rebate {
+                // Can't exceed what was held back from the sales, so saturating is exact.
+                let proceeds = clearing_price
+                    .saturating_sub(auction.floor_price)
+                    .saturating_mul(state.sold as Balance);
+                self.split_payment(None, proceeds);
+            }
+
+            Self::env().emit_event(AuctionEnded {
+                auction_id,
+                clearing_price,
+            });
+            Ok(clearing_price)
+        }
+
+        /// Refunds what the caller paid in an ended Dutch auction above its clearing price.
+        ///
+        /// Returns the amount refunded.
+        #[ink(message)]
+        #[modifiers(non_reentrant)]
+        pub fn claim_rebate(&mut self, auction_id: u32) -> Result<Balance, ProxyError> {
+            let clearing_price = self
+                .clearing_price(auction_id)
+                .ok_or(ProxyError::AuctionNotEnded)?;
+            let caller = Self::env().caller();
+            let (bought, paid) = self
+                .proxy
+                .auction_purchases
+                .get(&(auction_id, caller))
+                .ok_or(ProxyError::NoRebate)?;
+            self.proxy.auction_purchases.remove(&(auction_id, caller));
+
+            // Every token was bought at or above the clearing price, so the rebate can't go below
+            // zero.
+            let rebate = paid.saturating_sub((bought as Balance).saturating_mul(clearing_price));
+            if rebate > 0 {
+                Self::env()
+                    .transfer(caller, rebate)
+                    .map_err(|_| ProxyError::TransferFailed)?;
+            }
+            Ok(rebate)
+        }
+
+        /// Increases a delay of timelocked config changes, in milliseconds.
+        /// A shorter delay has to go through `propose_timelock_delay`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn set_timelock_delay(&mut self, new_delay: Timestamp) -> Result<(), ProxyError> {
+            ensure!(
+                new_delay >= self.timelock_delay(),
+                ProxyError::TimelockRequired
+            );
+            self.apply_change(ConfigChange::TimelockDelay(new_delay))
+        }
+
+        /// Proposes a new RMRK contract address, which can be set with
+        /// `execute_rmrk_contract_address` once the timelock delay passes.
+        ///
+        /// Returns the block timestamp from which the change can be executed.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn propose_rmrk_contract_address(
+            &mut self,
+            new_contract_address: AccountId,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::RmrkContract(new_contract_address)))
+        }
+
+        /// Sets the RMRK contract address proposed with `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn execute_rmrk_contract_address(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::RmrkContract)
+        }
+
+        /// Proposes a new catalog contract address. See `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn propose_catalog_contract_address(
+            &mut self,
+            new_contract_address: AccountId,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::CatalogContract(new_contract_address)))
+        }
+
+        /// Sets the catalog contract address proposed with `propose_catalog_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn execute_catalog_contract_address(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::CatalogContract)
+        }
+
+        /// Proposes a new minting price. See `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn propose_mint_price(
+            &mut self,
+            new_mint_price: Balance,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::MintPrice(new_mint_price)))
+        }
+
+        /// Sets the minting price proposed with `propose_mint_price`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn execute_mint_price(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::MintPrice)
+        }
+
+        /// Proposes a new timelock delay. The current delay applies to the proposal itself.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn propose_timelock_delay(
+            &mut self,
+            new_delay: Timestamp,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::TimelockDelay(new_delay)))
+        }
+
+        /// Sets the timelock delay proposed with `propose_timelock_delay`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn execute_timelock_delay(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::TimelockDelay)
+        }
+
+        /// Proposes replacing the contract code with code uploaded under `code_hash`.
+        /// See `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn propose_upgrade(&mut self, code_hash: [u8; 32]) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::Upgrade(code_hash)))
+        }
+
+        /// Replaces the contract code with code proposed with `propose_upgrade`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn execute_upgrade(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::Upgrade)
+        }
+
+        /// Proposes new payees. See `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn propose_payees(
+            &mut self,
+            payees: Vec<(AccountId, u16)>,
+        ) -> Result<Timestamp, ProxyError> {
+            ensure!(valid_payees(&payees), ProxyError::InvalidPayees);
+            Ok(self.propose(ConfigChange::Payees(payees)))
+        }
+
+        /// Sets the payees proposed with `propose_payees`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn execute_payees(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::Payees)
+        }
+
+        /// Proposes a new pricing mode. See `propose_rmrk_contract_address`.
+        /// Whether a running auction has ended is checked on execution.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn propose_pricing_mode(&mut self, mode: PricingMode) -> Result<Timestamp, ProxyError> {
+            valid_pricing_mode(&mode)?;
+            Ok(self.propose(ConfigChange::PricingMode(mode)))
+        }
+
+        /// Sets the pricing mode proposed with `propose_pricing_mode`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn execute_pricing_mode(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::PricingMode)
+        }
+
+        /// Proposes a new presale minting price. See `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn propose_presale_price(
+            &mut self,
+            new_presale_price: Option<Balance>,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::PresalePrice(new_presale_price)))
+        }
+
+        /// Sets the presale minting price proposed with `propose_presale_price`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn execute_presale_price(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::PresalePrice)
+        }
+
+        /// Proposes a new payment token. See `propose_rmrk_contract_address`.
+        /// Whether the token can be combined with the current settings is checked on execution.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn propose_payment_token(
+            &mut self,
+            token: Option<AccountId>,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::PaymentToken(token)))
+        }
+
+        /// Sets the payment token proposed with `propose_payment_token`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn execute_payment_token(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::PaymentToken)
+        }
+
+        /// Proposes a new price of the sale phase at `index`. See `propose_rmrk_contract_address`.
+        /// The phase must not have started, neither now nor on execution.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn propose_phase_price(
+            &mut self,
+            index: u32,
+            price: Balance,
+        ) -> Result<Timestamp, ProxyError> {
+            self.ensure_phase_not_started(index)?;
+            Ok(self.propose(ConfigChange::PhasePrice(index, price)))
+        }
+
+        /// Sets the price of the sale phase at `index` proposed with `propose_phase_price`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn execute_phase_price(&mut self, index: u32) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::PhasePrice(index))
+        }
+
+        /// Proposes a new source of randomness. See `propose_rmrk_contract_address`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn propose_randomness(
+            &mut self,
+            randomness: RandomnessConfig,
+        ) -> Result<Timestamp, ProxyError> {
+            Ok(self.propose(ConfigChange::Randomness(randomness)))
+        }
+
+        /// Sets the source of randomness proposed with `propose_randomness`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn execute_randomness(&mut self) -> Result<(), ProxyError> {
+            self.execute(ConfigKey::Randomness)
+        }
+
+        /// Cancels a pending change of the setting `key`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn cancel_proposal(&mut self, key: ConfigKey) -> Result<(), ProxyError> {
+            let mut proposals = self.proposals();
+            let index = proposals
+                .iter()
+                .position(|proposal| proposal.change.key() == key)
+                .ok_or(ProxyError::ProposalNotFound)?;
+            proposals.remove(index);
+            self.proxy.proposals.set(&proposals);
+
+            Self::env().emit_event(ProposalCancelled { key });
+            Ok(())
+        }
+
+        /// Sets a presale minting price. `None` means allowlisted accounts pay `mint_price`.
+        /// Possible only while the timelock delay is zero, otherwise see `propose_presale_price`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn set_presale_price(
+            &mut self,
+            new_presale_price: Option<Balance>,
+        ) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::PresalePrice(new_presale_price))
+        }
+
+        /// Sets a Merkle root of allowlisted accounts. Setting `None` ends the presale.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_allowlist_root(&mut self, new_root: Option<Hash>) -> Result<(), ProxyError> {
+            self.proxy.allowlist_root = new_root;
+            Ok(())
+        }
+
+        /// Sets a rarity weight of an asset. Asset with weight `w` is picked with probability
+        /// `w / sum_of_weights`. Setting zero weight removes the asset from the rarity table.
+        /// Mints fail with `AssetNotFound` while the table holds an asset the RMRK contract
+        /// doesn't define.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_asset_weight(
+            &mut self,
+            asset_id: AssetId,
+            weight: u32,
+        ) -> Result<(), ProxyError> {
+            ensure!(asset_id > 0, ProxyError::AssetNotFound);
+            let weights = &mut self.proxy.asset_weights;
+            match weights.iter().position(|(id, _)| *id == asset_id) {
+                Some(index) if weight == 0 => {
+                    weights.remove(index);
+                }
+                Some(index) => weights[index].1 = weight,
+                None if weight > 0 => weights.push((asset_id, weight)),
+                None => (),
+            }
+            Ok(())
+        }
+
+        /// Sets a remaining supply of an asset. Each mint assigning the asset decrements it and
+        /// the asset is no longer drawn once it reaches zero. `None` makes the supply unlimited.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_asset_supply(
+            &mut self,
+            asset_id: AssetId,
+            supply: Option<u32>,
+        ) -> Result<(), ProxyError> {
+            self.update_asset_supply(asset_id, supply);
+            Ok(())
+        }
+
+        /// Sets the source of randomness used for direct mints.
+        /// Possible only while the timelock delay is zero, otherwise see `propose_randomness`.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_randomness(&mut self, randomness: RandomnessConfig) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::Randomness(randomness))
+        }
+
+        /// Enables commit-reveal minting with given settings, or disables it with `None`.
+        /// Pending reservations can be refunded right away once commit-reveal is disabled.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_commit_reveal(
+            &mut self,
+            config: Option<CommitRevealConfig>,
+        ) -> Result<(), ProxyError> {
+            if let Some(config) = &config {
+                ensure!(
+                    config.reveal_delay > 0 && config.reveal_window > 0,
+                    ProxyError::InvalidCommitRevealConfig
+                );
+                ensure!(
+                    self.payment_token().is_none(),
+                    ProxyError::NativePaymentRequired
+                );
+            }
+            self.proxy.commit_reveal = config;
+            Ok(())
+        }
+
+        /// Sets the share of the payment, in basis points, kept when an expired reservation
+        /// is refunded.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_expiry_fee(&mut self, fee: u16) -> Result<(), ProxyError> {
+            ensure!(fee <= BASIS_POINTS, ProxyError::InvalidCommitRevealConfig);
+            self.proxy.expiry_fee.set(&fee);
+            Ok(())
+        }
+
+        /// Adds a sale phase to the end of the schedule.
+        /// A phase must not have started yet and must begin after the last scheduled phase ends.
+        /// While a timelock delay is set, it must start no earlier than the delay from now.
+        /// The phase comes with a price, so the caller needs the `PRICE_MANAGER` role as well.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER), only_role(PRICE_MANAGER))]
+        pub fn add_phase(&mut self, phase: SalePhase) -> Result<u32, ProxyError> {
+            self.ensure_phase_ahead(phase.start)?;
+            let mut phases = self.proxy.phases.clone();
+            phases.push(phase);
+            ensure!(valid_schedule(&phases), ProxyError::InvalidPhase);
+
+            self.proxy.phases = phases;
+            Ok(self.proxy.phases.len() as u32 - 1)
+        }
+
+        /// Replaces a sale phase which has not started yet. The new start is checked as in
+        /// `add_phase`. Changing the phase price needs the `PRICE_MANAGER` role as well and
+        /// goes through `propose_phase_price` while a timelock delay is set.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn update_phase(&mut self, index: u32, phase: SalePhase) -> Result<(), ProxyError> {
+            self.ensure_phase_not_started(index)?;
+            self.ensure_phase_ahead(phase.start)?;
+            if phase.price != self.proxy.phases[index as usize].price {
+                ensure!(
+                    self.has_role(PRICE_MANAGER, self.env().caller()),
+                    AccessControlError::MissingRole
+                );
+                ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            }
+            let mut phases = self.proxy.phases.clone();
+            phases[index as usize] = phase;
+            ensure!(valid_schedule(&phases), ProxyError::InvalidPhase);
+
+            self.proxy.phases = phases;
+            Ok(())
+        }
+
+        /// Removes a sale phase which has not started yet. Later phases move down by one, so
+        /// pending price proposals of the removed and later phases are cancelled.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn remove_phase(&mut self, index: u32) -> Result<(), ProxyError> {
+            self.ensure_phase_not_started(index)?;
+            self.proxy.phases.remove(index as usize);
+
+            let (cancelled, proposals): (Vec<_>, Vec<_>) = self.proposals().into_iter().partition(
+                |proposal| matches!(proposal.change.key(), ConfigKey::PhasePrice(i) if i >= index),
+            );
+            self.proxy.proposals.set(&proposals);
+            for proposal in cancelled {
+                Self::env().emit_event(ProposalCancelled {
+                    key: proposal.change.key(),
+                });
+            }
+            Ok(())
+        }
+
+        /// Sets the price of a sale phase which has not started yet.
+        /// Possible only while the timelock delay is zero, otherwise see `propose_phase_price`.
+        #[ink(message)]
+        #[modifiers(only_role(PRICE_MANAGER))]
+        pub fn set_phase_price(&mut self, index: u32, price: Balance) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            self.apply_change(ConfigChange::PhasePrice(index, price))
+        }
+
+        /// Sets a maximum number of tokens which can be minted in a single call.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_max_per_tx(&mut self, new_max_per_tx: u32) -> Result<(), ProxyError> {
+            ensure!(new_max_per_tx > 0, ProxyError::BadMintCount);
+            self.proxy.max_per_tx = new_max_per_tx;
+            Ok(())
+        }
+
+        /// Sets a maximum number of tokens a single account can mint in a sale phase.
+        /// Phases with their own `max_per_wallet` override this value.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_max_per_wallet(
+            &mut self,
+            new_max_per_wallet: Option<u32>,
+        ) -> Result<(), ProxyError> {
+            self.proxy.max_per_wallet = new_max_per_wallet;
+            Ok(())
+        }
+
+        /// Sets a maximum number of tokens which can be minted through the proxy, reserve included.
+        /// `None` makes the supply unlimited.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_max_supply(&mut self, new_max_supply: Option<u32>) -> Result<(), ProxyError> {
+            ensure!(
+                valid_supply(new_max_supply, self.proxy.total_minted, self.proxy.reserve),
+                ProxyError::InvalidSupply
+            );
+            self.proxy.max_supply = new_max_supply;
+            Ok(())
+        }
+
+        /// Sets a number of tokens left for `reserve_mint`, which public minting can't consume.
+        #[ink(message)]
+        #[modifiers(only_role(CONFIG_MANAGER))]
+        pub fn set_reserve(&mut self, new_reserve: u32) -> Result<(), ProxyError> {
+            ensure!(
+                valid_supply(self.proxy.max_supply, self.proxy.total_minted, new_reserve),
+                ProxyError::InvalidSupply
+            );
+            self.proxy.reserve = new_reserve;
+            Ok(())
+        }
+
+        /// Sets payees of future mint proceeds with their shares in basis points,
+        /// which have to add up to `BASIS_POINTS`. Pending payments are kept.
+        /// Possible only while the timelock delay is zero, otherwise see `propose_payees`.
+        #[ink(message)]
+        #[modifiers(only_role(ADMIN))]
+        pub fn set_payees(&mut self, payees: Vec<(AccountId, u16)>) -> Result<(), ProxyError> {
+            ensure!(self.timelock_delay() == 0, ProxyError::TimelockRequired);
+            ensure!(valid_payees(&payees), ProxyError::InvalidPayees);
+            self.apply_change(ConfigChange::Payees(payees))
+        }
+
+        /// Sets defaults of a new layout 1 storage and grants `admin` every role and all proceeds.
+        fn init(&mut self, admin: AccountId) {
+            self.proxy.max_per_tx = DEFAULT_MAX_PER_TX;
+            self.proxy.storage_version.set(&STORAGE_VERSION);
+            self.proxy.payees.push((admin, BASIS_POINTS));
+            self._init_with_admin(admin);
+            for role in [PRICE_MANAGER, CONFIG_MANAGER, PAUSER, RESERVE_MINTER] {
+                self._setup_role(role, admin);
+            }
+        }
+
+        /// Stores `change` to be executed after the timelock delay, replacing a pending change
+        /// of the same setting. Returns the block timestamp from which it can be executed.
+        fn propose(&mut self, change: ConfigChange) -> Timestamp {
+            let executable_at = Self::env()
+                .block_timestamp()
+                .saturating_add(self.timelock_delay());
+            let mut proposals = self.proposals();
+            proposals.retain(|proposal| proposal.change.key() != change.key());
+            proposals.push(Proposal {
+                change: change.clone(),
+                executable_at,
+            });
+            self.proxy.proposals.set(&proposals);
+
+            Self::env().emit_event(ChangeProposed {
+                change,
+                executable_at,
+            });
+            executable_at
+        }
+
+        /// Applies a pending change of the setting `key` whose timelock delay has passed.
+        fn execute(&mut self, key: ConfigKey) -> Result<(), ProxyError> {
+            let mut proposals = self.proposals();
+            let index = proposals
+                .iter()
+                .position(|proposal| proposal.change.key() == key)
+                .ok_or(ProxyError::ProposalNotFound)?;
+            ensure!(
+                Self::env().block_timestamp() >= proposals[index].executable_at,
+                ProxyError::TimelockNotExpired
+            );
+            let proposal = proposals.remove(index);
+            self.apply_change(proposal.change)?;
+            self.proxy.proposals.set(&proposals);
+            Ok(())
+        }
+
+        fn apply_change(&mut self, change: ConfigChange) -> Result<(), ProxyError> {
+            match change {
+                ConfigChange::RmrkContract(contract) => {
+                    self.proxy.rmrk_contract = Some(contract);
+                    Self::env().emit_event(RmrkContractChanged { contract });
+                }
+                ConfigChange::CatalogContract(contract) => {
+                    self.proxy.catalog_contract = Some(contract);
+                    Self::env().emit_event(CatalogContractChanged { contract });
+                }
+                ConfigChange::MintPrice(price) => {
+                    self.proxy.mint_price = price;
+                    Self::env().emit_event(MintPriceChanged { price });
+                }
+                ConfigChange::TimelockDelay(delay) => self.proxy.timelock_delay.set(&delay),
+                ConfigChange::Upgrade(code_hash) => {
+                    ink::env::set_code_hash(&code_hash).map_err(|_| ProxyError::UpgradeFailed)?;
+                    Self::env().emit_event(Upgraded { code_hash });
+                }
+                ConfigChange::Payees(payees) => self.proxy.payees = payees,
+                ConfigChange::PricingMode(mode) => {
+                    valid_pricing_mode(&mode)?;
+                    if let PricingMode::DutchAuction(_) = self.pricing_mode() {
+                        ensure!(
+                            self.auction_state().sold == 0
+                                || self.proxy.clearing_prices.contains(&self.auction_id()),
+                            ProxyError::AuctionNotEnded
+                        );
+                    }
+                    if let PricingMode::DutchAuction(auction) = &mode {
+                        ensure!(
+                            !auction.rebate || self.payment_token().is_none(),
+                            ProxyError::NativePaymentRequired
+                        );
+                        self.proxy.auction_id.set(&(self.auction_id() + 1));
+                        self.proxy.auction.set(&AuctionState::default());
+                    }
+                    self.proxy.pricing.set(&mode);
+                }
+                ConfigChange::PresalePrice(price) => self.proxy.presale_price = price,
+                ConfigChange::PaymentToken(token) => {
+                    if token.is_some() {
+                        let rebate_auction = match self.pricing_mode() {
+                            PricingMode::DutchAuction(auction) => {
+                                auction.rebate
+                                    && !self.proxy.clearing_prices.contains(&self.auction_id())
+                            }
+                            _ => false,
+                        };
+                        ensure!(
+                            self.proxy.commit_reveal.is_none() && !rebate_auction,
+                            ProxyError::NativePaymentRequired
+                        );
+                    }
+                    self.proxy.payment_token.set(&token);
+                }
+                ConfigChange::PhasePrice(index, price) => {
+                    self.ensure_phase_not_started(index)?;
+                    self.proxy.phases[index as usize].price = price;
+                }
+                ConfigChange::Randomness(randomness) => self.proxy.randomness = randomness,
+            }
+            Ok(())
+        }
+
+        /// Gets terms of the sale active at the current block timestamp.
+        ///
+        /// Without scheduled phases the sale is always open, using `mint_price`
+        /// or `presale_price` while the allowlist root is set.
+        fn sale_terms(&self) -> Result<SaleTerms, ProxyError> {
+            if self.proxy.phases.is_empty() {
+                let price = match self.proxy.allowlist_root {
+                    Some(_) => self.proxy.presale_price.unwrap_or(self.proxy.mint_price),
+                    None => self.public_price()?,
+                };
+                return Ok(SaleTerms {
+                    phase: self.proxy.allowlist_root.map(|_| PRESALE_PHASE),
+                    price,
+                    max_per_wallet: self.proxy.max_per_wallet,
+                    allowlist_root: self.proxy.allowlist_root,
+                })
+            }
+
+            let index = self.active_phase().ok_or(ProxyError::SaleNotActive)?;
+            let phase = &self.proxy.phases[index as usize];
+            Ok(SaleTerms {
+                phase: Some(index),
+                price: phase.price,
+                max_per_wallet: phase.max_per_wallet.or(self.proxy.max_per_wallet),
+                allowlist_root: phase.allowlist_root,
+            })
+        }
+
+        /// Verifies that `account` can mint `count` more tokens under given sale terms
+        /// and records them.
+        fn consume_allowance(
+            &mut self,
+            account: AccountId,
+            count: u32,
+            terms: &SaleTerms,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<(), ProxyError> {
+            let minted = self
+                .phase_minted(terms.phase, account)
+                .checked_add(count)
+                .ok_or(ProxyError::PerWalletLimitExceeded)?;
+
+            if let Some(root) = terms.allowlist_root {
+                let allowlist = allowlist.ok_or(ProxyError::NotAllowlisted)?;
+                let leaf = merkle::allowlist_leaf(&account, allowlist.quota);
+                ensure!(
+                    merkle::verify(&root, leaf, &allowlist.proof),
+                    ProxyError::NotAllowlisted
+                );
+                ensure!(
+                    minted <= allowlist.quota,
+                    ProxyError::AllowlistQuotaExceeded
+                );
+            }
+            if let Some(max_per_wallet) = terms.max_per_wallet {
+                ensure!(minted <= max_per_wallet, ProxyError::PerWalletLimitExceeded);
+            }
+
+            self.proxy
+                .phase_minted
+                .insert(&(terms.phase, account), &minted);
+            let total_minted = self.minted_by(account).saturating_add(count);
+            self.proxy.minted.insert(&account, &total_minted);
+            Ok(())
+        }
+
+        /// Charges the caller for `count` tokens at the active sale price and mints them to `recipient`.
+        /// With a payment token set, the cost is taken out of the allowance the caller gave the proxy.
+        fn mint_tokens(
+            &mut self,
+            recipient: AccountId,
+            count: u32,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<Vec<Id>, ProxyError> {
+            let rmrk_contract = self.rmrk_contract()?;
+            let payments = self.collect_payment(recipient, count, allowlist)?;
+
+            let total_assets = self.total_assets(rmrk_contract)?;
+            let mut randomness = self.randomness_source();
+            let mut token_ids = Vec::with_capacity(count as usize);
+            for payment in payments {
+                let asset_id = self.pick_asset(total_assets, randomness.as_mut())?;
+                let token_id = self.mint_token(rmrk_contract, asset_id, payment, recipient)?;
+                token_ids.push(token_id);
+            }
+
+            Ok(token_ids)
+        }
+
+        /// Takes the payment for `count` tokens minted directly to `recipient` and records
+        /// the sale. Overpayment of a dynamic price is refunded to the caller.
+        ///
+        /// Returns the payment of each token.
+        fn collect_payment(
+            &mut self,
+            recipient: AccountId,
+            count: u32,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<Vec<Payment>, ProxyError> {
+            ensure!(
+                self.proxy.commit_reveal.is_none(),
+                ProxyError::CommitRevealRequired
+            );
+
+            ensure!(count > 0, ProxyError::BadMintCount);
+            ensure!(
+                count <= self.proxy.max_per_tx,
+                ProxyError::PerTxLimitExceeded
+            );
+            let terms = self.sale_terms()?;
+            let pricing = self.dynamic_pricing(&terms);
+            let prices = self.token_prices(&terms, count);
+            let cost = self
+                .batch_cost(&terms, count)
+                .ok_or(ProxyError::BadMintValue)?;
+            let payment_token = self.payment_token();
+            let transferred_value = Self::env().transferred_value();
+            // Dynamic prices may move before the call lands, so overpayment is refunded.
+            let paid = match payment_token {
+                Some(_) => transferred_value == 0,
+                None => {
+                    transferred_value == cost || (pricing.is_some() && transferred_value > cost)
+                }
+            };
+            ensure!(paid, ProxyError::BadMintValue);
+            self.consume_allowance(recipient, count, &terms, allowlist)?;
+            self.consume_public_supply(count)?;
+            self.proxy.sold.set(&self.sold().saturating_add(count));
+            self.mix_entropy(&(
+                Self::env().caller(),
+                recipient,
+                count,
+                Self::env().block_timestamp(),
+            ));
+            if let Some(token) = payment_token {
+                client::psp22_transfer_from(
+                    token,
+                    Self::env().caller(),
+                    Self::env().account_id(),
+                    cost,
+                )?;
+            }
+
+            // With rebates, the part above the floor price waits for the clearing price.
+            let auction = match pricing {
+                Some(PricingMode::DutchAuction(auction)) => Some(auction),
+                _ => None,
+            };
+            let held = match &auction {
+                Some(auction) if auction.rebate => terms.price - auction.floor_price,
+                _ => 0,
+            };
+            if let Some(auction) = auction {
+                self.record_auction_sale(&auction, count, terms.price, cost)?;
+            }
+            if transferred_value > cost {
+                Self::env()
+                    .transfer(Self::env().caller(), transferred_value - cost)
+                    .map_err(|_| ProxyError::TransferFailed)?;
+            }
+
+            Ok(prices
+                .into_iter()
+                .map(|price| {
+                    match payment_token {
+                        Some(token) => Payment::Token { token, price },
+                        None => Payment::Native { price, held },
+                    }
+                })
+                .collect())
+        }
+
+        /// Takes the payment for a reservation of one token and records it.
+        fn commit_reservation(
+            &mut self,
+            commitment: Hash,
+            allowlist: Option<AllowlistProof>,
+        ) -> Result<u64, ProxyError> {
+            let terms = self.sale_terms()?;
+            ensure!(
+                self.dynamic_pricing(&terms).is_none(),
+                ProxyError::FixedPriceRequired
+            );
+            let transferred_value = Self::env().transferred_value();
+            ensure!(transferred_value == terms.price, ProxyError::BadMintValue);
+            let caller = Self::env().caller();
+            self.consume_allowance(caller, 1, &terms, allowlist)?;
+            self.consume_public_supply(1)?;
+            self.mix_entropy(&(caller, commitment, Self::env().block_timestamp()));
+
+            let reservation_id = self.proxy.next_reservation_id;
+            self.proxy.reservations.insert(
+                &reservation_id,
+                &Reservation {
+                    owner: caller,
+                    commitment,
+                    committed_at: Self::env().block_number(),
+                    paid: transferred_value,
+                    phase: terms.phase,
+                },
+            );
+            self.proxy.next_reservation_id += 1;
+
+            Ok(reservation_id)
+        }
+
+        /// Records `rmrk_price`, the lazy mint price of the RMRK contract the reveal of
+        /// a reservation is going to pay. The reservation payment has to cover it.
+        fn lock_rmrk_price(
+            &mut self,
+            reservation_id: u64,
+            rmrk_price: Balance,
+        ) -> Result<(), ProxyError> {
+            ensure!(
+                Self::env().transferred_value() >= rmrk_price,
+                ProxyError::RmrkPriceNotCovered
+            );
+            self.proxy
+                .reservation_rmrk_prices
+                .insert(&reservation_id, &rmrk_price);
+            Ok(())
+        }
+
+        /// Gets the pricing mode if it prices given sale terms and isn't fixed.
+        fn dynamic_pricing(&self, terms: &SaleTerms) -> Option<PricingMode> {
+            match self.pricing_mode() {
+                PricingMode::Fixed => None,
+                mode if terms.phase.is_none() && terms.allowlist_root.is_none() => Some(mode),
+                _ => None,
+            }
+        }
+
+        /// Gets prices of the next `count` tokens minted under given sale terms.
+        fn token_prices(&self, terms: &SaleTerms, count: u32) -> Vec<Balance> {
+            match self.dynamic_pricing(terms) {
+                Some(PricingMode::BondingCurve(curve)) => {
+                    (0..count)
+                        .map(|index| {
+                            pricing::price_after(&curve, self.sold().saturating_add(index))
+                        })
+                        .collect()
+                }
+                _ => (0..count).map(|_| terms.price).collect(),
+            }
+        }
+
+        /// Gets a total price of the next `count` tokens minted under given sale terms,
+        /// `None` on overflow.
+        fn batch_cost(&self, terms: &SaleTerms, count: u32) -> Option<Balance> {
+            match self.dynamic_pricing(terms) {
+                Some(PricingMode::BondingCurve(curve)) => {
+                    pricing::batch_cost(&curve, self.sold(), count)
+                }
+                _ => terms.price.checked_mul(count as Balance),
+            }
+        }
+
+        /// Records `count` tokens sold by the caller for `cost` in total at `price` each.
+        fn record_auction_sale(
+            &mut self,
+            auction: &DutchAuction,
+            count: u32,
+            price: Balance,
+            cost: Balance,
+        ) -> Result<(), ProxyError> {
+            let mut state = self.proxy.auction.get().unwrap_or_default();
+            state.sold = state.sold.saturating_add(count);
+            state.last_price = price;
+            self.proxy.auction.set(&state);
+
+            if auction.rebate {
+                let key = (self.auction_id(), Self::env().caller());
+                let (bought, paid) = self.proxy.auction_purchases.get(&key).unwrap_or((0, 0));
+                let bought = bought.checked_add(count).ok_or(ProxyError::BadMintCount)?;
+                let paid = paid.checked_add(cost).ok_or(ProxyError::BadMintValue)?;
+                self.proxy.auction_purchases.insert(&key, &(bought, paid));
+            }
+            Ok(())
+        }
+
+        /// Checks whether the public sale without phases is a Dutch auction which has ended.
+        fn auction_ended(&self) -> bool {
+            matches!(self.pricing_mode(), PricingMode::DutchAuction(_))
+                && self.proxy.clearing_prices.contains(&self.auction_id())
+        }
+
+        /// Gets a price of the public sale without phases at the current block timestamp.
+        fn public_price(&self) -> Result<Balance, ProxyError> {
+            match self.pricing_mode() {
+                PricingMode::Fixed => Ok(self.proxy.mint_price),
+                PricingMode::DutchAuction(auction) => {
+                    let now = Self::env().block_timestamp();
+                    ensure!(
+                        now >= auction.start
+                            && !self.proxy.clearing_prices.contains(&self.auction_id()),
+                        ProxyError::SaleNotActive
+                    );
+                    Ok(auction.price_at(now))
+                }
+                PricingMode::BondingCurve(curve) => Ok(pricing::price_after(&curve, self.sold())),
+            }
+        }
+
+        /// Counts `count` more publicly minted tokens, which must leave the reserve untouched.
+        fn consume_public_supply(&mut self, count: u32) -> Result<(), ProxyError> {
+            let total_minted = self
+                .proxy
+                .total_minted
+                .checked_add(count)
+                .ok_or(ProxyError::SoldOut)?;
+            ensure!(
+                valid_supply(self.proxy.max_supply, total_minted, self.proxy.reserve),
+                ProxyError::SoldOut
+            );
+            self.proxy.total_minted = total_minted;
+            Ok(())
+        }
+
+        /// Gets a number of tokens sold by direct mints, which positions the bonding curve.
+        /// Reservations and reserve mints don't count.
+        fn sold(&self) -> u32 {
+            self.proxy.sold.get().unwrap_or(0)
+        }
+
+        /// Gives back a mint recorded by `consume_allowance`.
+        fn release_allowance(&mut self, account: AccountId, phase: Option<u32>) {
+            let phase_minted = self.phase_minted(phase, account).saturating_sub(1);
+            self.proxy
+                .phase_minted
+                .insert(&(phase, account), &phase_minted);
+            let total_minted = self.minted_by(account).saturating_sub(1);
+            self.proxy.minted.insert(&account, &total_minted);
+        }
+
+        /// Gets a reservation owned by the caller.
+        fn owned_reservation(&self, reservation_id: u64) -> Result<Reservation, ProxyError> {
+            let reservation = self
+                .proxy
+                .reservations
+                .get(&reservation_id)
+                .ok_or(ProxyError::ReservationNotFound)?;
+            ensure!(
+                reservation.owner == Self::env().caller(),
+                ProxyError::NotReservationOwner
+            );
+            Ok(reservation)
+        }
+
+        fn ensure_phase_not_started(&self, index: u32) -> Result<(), ProxyError> {
+            let phase = self
+                .proxy
+                .phases
+                .get(index as usize)
+                .ok_or(ProxyError::PhaseNotFound)?;
+            ensure!(
+                phase.start > self.env().block_timestamp(),
+                ProxyError::PhaseAlreadyStarted
+            );
+            Ok(())
+        }
+
+        /// Checks that a phase starting at `start` is scheduled ahead of time. While a timelock
+        /// delay is set, phase prices have to be known at least the delay before they apply.
+        fn ensure_phase_ahead(&self, start: Timestamp) -> Result<(), ProxyError> {
+            let now = self.env().block_timestamp();
+            ensure!(start > now, ProxyError::PhaseAlreadyStarted);
+            ensure!(
+                start >= now.saturating_add(self.timelock_delay()),
+                ProxyError::TimelockRequired
+            );
+            Ok(())
+        }
+
+        /// Gets the RMRK contract address or fails if it is not set.
+        fn rmrk_contract(&self) -> Result<AccountId, ProxyError> {
+            self.proxy.rmrk_contract.ok_or(ProxyError::NotConfigured)
+        }
+
+        /// Reads a number of asset entries defined on the RMRK contract.
+        fn total_assets(&self, rmrk_contract: AccountId) -> Result<u32, ProxyError> {
+            let total_assets = client::total_assets(rmrk_contract)?;
+            ensure!(total_assets > 0, ProxyError::NoAssetsDefined);
+
+            Ok(total_assets)
+        }
+
+        /// Mints a single token paying the RMRK contract its price, adds `asset_id` to it and
+        /// transfers it to `recipient`. The rest of `payment` is split among payees.
+        fn mint_token(
+            &mut self,
+            rmrk_contract: AccountId,
+            asset_id: AssetId,
+            payment: Payment,
+            recipient: AccountId,
+        ) -> Result<Id, ProxyError> {
+            let rmrk_price = match payment {
+                Payment::Committed { rmrk_price, .. } => rmrk_price,
+                _ => client::price(rmrk_contract)?,
+            };
+            let covered = match payment {
+                Payment::Committed { .. } => true,
+                Payment::Native { price, held } => price.saturating_sub(held) >= rmrk_price,
+                Payment::Reserve | Payment::Token { .. } => rmrk_price == 0,
+            };
+            ensure!(covered, ProxyError::RmrkPriceNotCovered);
+            let token_id = client::mint(rmrk_contract, rmrk_price)?;
+            client::add_asset_to_token(rmrk_contract, token_id.clone(), asset_id)?;
+            client::transfer(rmrk_contract, recipient, token_id.clone())?;
+
+            let price = match payment {
+                Payment::Reserve => None,
+                Payment::Native { price, held } => {
+                    self.split_payment(None, price - held - rmrk_price);
+                    Some(price)
+                }
+                Payment::Token { token, price } => {
+                    self.split_payment(Some(token), price);
+                    Some(price)
+                }
+                Payment::Committed { price, rmrk_price } => {
+                    self.split_payment(None, price - rmrk_price);
+                    Some(price)
+                }
+            };
+
+            Self::env().emit_event(TokenMinted {
+                id: token_id.clone(),
+                price,
+                recipient,
+                asset_id,
+            });
+            Ok(token_id)
+        }
+
+        /// Credits `proceeds` in PSP22 `token`, or the native token if `None`, to payees by their
+        /// shares. Rounding leftovers go to the first payee.
+        fn split_payment(&mut self, token: Option<AccountId>, proceeds: Balance) {
+            let payees = self.proxy.payees.clone();
+            let mut leftover = proceeds;
+            for (payee, share) in &payees {
+                let amount = share_of(proceeds, *share);
+                leftover -= amount;
+                self.credit_payment(token, *payee, amount);
+            }
+            if let Some((payee, _)) = payees.first() {
+                self.credit_payment(token, *payee, leftover);
+            }
+        }
+
+        /// Adds `amount` to pending payments of `payee` in PSP22 `token` or the native token.
+        fn credit_payment(&mut self, token: Option<AccountId>, payee: AccountId, amount: Balance) {
+            match token {
+                Some(token) => {
+                    let pending = self
+                        .pending_token_payment(token, payee)
+                        .saturating_add(amount);
+                    self.proxy
+                        .pending_token_payments
+                        .insert(&(token, payee), &pending);
+                }
+                None => {
+                    let pending = self.pending_payment(payee).saturating_add(amount);
+                    self.proxy.pending_payments.insert(&payee, &pending);
+                }
+            }
+        }
+
+        /// Picks a random asset for a token among assets with remaining supply and decrements
+        /// its supply. Assets are equally likely unless the rarity table is set.
+        fn pick_asset(
+            &mut self,
+            total_assets: u32,
+            randomness: &mut dyn RandomnessSource,
+        ) -> Result<AssetId, ProxyError> {
+            let exhausted = &self.proxy.exhausted_assets;
+            let asset_id = if self.proxy.asset_weights.is_empty() {
+                let exhausted_count = exhausted.iter().filter(|id| **id <= total_assets).count();
+                let available = total_assets - exhausted_count as u32;
+                ensure!(available > 0, ProxyError::SoldOut);
+                let index = self.get_random(available as u64, randomness)? as u32;
+                nth_available(&self.proxy.exhausted_assets, index)
+            } else {
+                ensure!(
+                    self.proxy
+                        .asset_weights
+                        .iter()
+                        .all(|(id, _)| *id <= total_assets),
+                    ProxyError::AssetNotFound
+                );
+                let available = available_weights(&self.proxy.asset_weights, exhausted);
+                ensure!(!available.is_empty(), ProxyError::SoldOut);
+                let roll = self.get_random(total_weight(&available), randomness)?;
+                pick_weighted(&available, roll)
+            };
+
+            self.decrement_asset_supply(asset_id);
+            Ok(asset_id)
+        }
+
+        /// Takes an explicitly chosen asset for a token and decrements its supply.
+        fn take_asset(
+            &mut self,
+            asset_id: AssetId,
+            total_assets: u32,
+        ) -> Result<AssetId, ProxyError> {
+            ensure!(
+                asset_id > 0 && asset_id <= total_assets,
+                ProxyError::AssetNotFound
+            );
+            ensure!(
+                self.proxy
+                    .exhausted_assets
+                    .binary_search(&asset_id)
+                    .is_err(),
+                ProxyError::SoldOut
+            );
+            self.decrement_asset_supply(asset_id);
+            Ok(asset_id)
+        }
+
+        fn decrement_asset_supply(&mut self, asset_id: AssetId) {
+            if let Some(remaining) = self.proxy.asset_supply.get(&asset_id) {
+                self.update_asset_supply(asset_id, Some(remaining - 1));
+            }
+        }
+
+        /// Stores remaining supply of an asset and keeps the sorted list of exhausted assets in sync.
+        fn update_asset_supply(&mut self, asset_id: AssetId, supply: Option<u32>) {
+            match supply {
+                Some(remaining) => {
+                    self.proxy.asset_supply.insert(&asset_id, &remaining);
+                }
+                None => {
+                    self.proxy.asset_supply.remove(&asset_id);
+                }
+            }
+
+            let exhausted = &mut self.proxy.exhausted_assets;
+            match exhausted.binary_search(&asset_id) {
+                Err(index) if supply == Some(0) => exhausted.insert(index, asset_id),
+                Ok(index) if supply != Some(0) => {
+                    exhausted.remove(index);
+                }
+                _ => (),
+            }
+        }
+
+        /// Generates random number in `0..bound`, Used to pick a random asset for a token.
+        ///
+        /// Uses rejection sampling over 8 byte chunks of the seed, so every number is equally likely.
+        /// Seeds are requested with a salt which changes on every call. `bound` must not be zero.
+        fn get_random(
+            &mut self,
+            bound: u64,
+            randomness: &mut dyn RandomnessSource,
+        ) -> Result<u64, ProxyError> {
+            // Largest multiple of `bound` within the u64 range, draws at or above it are biased.
+            let zone = (1u128 << 64) - (1u128 << 64) % bound as u128;
+            loop {
+                let salt = self.proxy.salt;
+                self.proxy.salt += 1;
+                let seed = randomness.random_seed(&salt.to_be_bytes())?;
+                for chunk in seed.chunks_exact(8) {
+                    let mut bytes = [0u8; 8];
+                    bytes.copy_from_slice(chunk);
+                    let draw = u64::from_be_bytes(bytes);
+                    if (draw as u128) < zone {
+                        return Ok(draw % bound)
+                    }
+                }
+            }
+        }
+
+        /// Creates the configured source of randomness for direct mints.
+        fn randomness_source(&self) -> Box<dyn RandomnessSource> {
+            match self.proxy.randomness {
+                RandomnessConfig::PseudoRandom => {
+                    Box::new(PseudoRandom::new(Self::env().block_timestamp()))
+                }
+                RandomnessConfig::Oracle { contract, selector } => {
+                    Box::new(OracleRandom::new(contract, selector))
+                }
+            }
+        }
+
+        /// Mixes `data` into the entropy pool. The first mix in a block records the pool as it was
+        /// at the block start and links the record of the previous mixing block to it.
+        fn mix_entropy<T: scale::Encode>(&mut self, data: &T) {
+            let block_number = Self::env().block_number();
+            let latest = self.proxy.entropy.get();
+            let pool = latest.map(|(_, pool)| pool).unwrap_or_default();
+            match latest {
+                Some((latest_block, _)) if latest_block == block_number => (),
+                _ => {
+                    if let Some((latest_block, _)) = latest {
+                        if let Some((record, _)) = self.proxy.entropy_records.get(&latest_block) {
+                            self.proxy
+                                .entropy_records
+                                .insert(&latest_block, &(record, Some(block_number)));
+                        }
+                    }
+                    self.proxy
+                        .entropy_records
+                        .insert(&block_number, &(pool, None));
+                }
+            }
+
+            let mut output = <hash::Keccak256 as hash::HashOutput>::Type::default();
+            ink::env::hash_encoded::<hash::Keccak256, _>(&(pool, data), &mut output);
+            self.proxy.entropy.set(&(block_number, Hash::from(output)));
+        }
+
+        /// Gets the entropy pool as it was at the start of block `target`, following records
+        /// from the one made in block `from`. This is the record of the first mixing block at or
+        /// after `target`, as nothing was mixed in between. `None` if there is no such block yet.
+        fn entropy_at(&self, from: BlockNumber, target: BlockNumber) -> Option<Hash> {
+            let mut block_number = from;
+            loop {
+                let (pool, next) = self.proxy.entropy_records.get(&block_number)?;
+                if block_number >= target {
+                    return Some(pool)
+                }
+                block_number = next?;
+            }
+        }
+
+        /// Mixes the revealed secret into the entropy pool and creates the source an asset of
+        /// the reservation is picked with.
+        ///
+        /// The pool as of block `reveal_from` holds every commit, reveal and mint
+        /// made after the commit, which the minter can't know when committing to the secret, and
+        /// it doesn't change once the reservation can be revealed. The minter learns the outcome
+        /// before revealing and may let the reservation expire instead, but can't pick another.
+        fn reveal_randomness(
+            &mut self,
+            reservation_id: u64,
+            reservation: &Reservation,
+            secret: Hash,
+            reveal_from: BlockNumber,
+        ) -> Result<CommitRevealRandom, ProxyError> {
+            self.mix_entropy(&(reservation_id, secret));
+            let entropy = self
+                .entropy_at(reservation.committed_at, reveal_from)
+                .ok_or(ProxyError::RevealTooEarly)?;
+            Ok(CommitRevealRandom::new(reservation_id, secret, entropy))
+        }
+    }
+
+    /// How a token minted with `mint_token` is paid for.
+    enum Payment {
+        /// Only the RMRK lazy mint price is covered, by the reserve minter.
+        Reserve,
+        /// `price` in the native token, of which `held` waits for an auction clearing price.
+        Native { price: Balance, held: Balance },
+        /// `price` in PSP22 `token`, which leaves nothing to pay the RMRK lazy mint price with.
+        Token { token: AccountId, price: Balance },
+        /// `price` in the native token paid at commit, when `rmrk_price` was locked in.
+        Committed { price: Balance, rmrk_price: Balance },
+    }
+
+    /// Computes a commitment to `secret` bound to the account which is going to reveal it.
+    fn commitment_hash(secret: &Hash, account: &AccountId) -> Hash {
+        let mut output = <hash::Keccak256 as hash::HashOutput>::Type::default();
+        ink::env::hash_encoded::<hash::Keccak256, _>(&(secret, account), &mut output);
+        Hash::from(output)
+    }
+
+    /// Computes `share` basis points of `amount`, rounded down. Splits `amount` before
+    /// multiplying, so it can't overflow.
+    fn share_of(amount: Balance, share: u16) -> Balance {
+        let basis_points = BASIS_POINTS as Balance;
+        let share = share as Balance;
+        amount / basis_points * share + amount % basis_points * share / basis_points
+    }
+
+    /// Checks that payee shares are positive and add up to `BASIS_POINTS`.
+    fn valid_payees(payees: &[(AccountId, u16)]) -> bool {
+        let total_shares: u32 = payees.iter().map(|(_, share)| *share as u32).sum();
+        total_shares == BASIS_POINTS as u32 && payees.iter().all(|(_, share)| *share > 0)
+    }
+
+    /// Checks parameters of a Dutch auction or a bonding curve.
+    fn valid_pricing_mode(mode: &PricingMode) -> Result<(), ProxyError> {
+        match mode {
+            PricingMode::DutchAuction(auction) => {
+                ensure!(
+                    auction.interval > 0 && auction.floor_price <= auction.start_price,
+                    ProxyError::InvalidAuction
+                )
+            }
+            PricingMode::BondingCurve(curve) => {
+                ensure!(curve.cap >= curve.base_price, ProxyError::InvalidCurve)
+            }
+            PricingMode::Fixed => (),
+        }
+        Ok(())
+    }
+
+    /// Checks that `total_minted` tokens and the reserve fit into `max_supply`.
+    fn valid_supply(max_supply: Option<u32>, total_minted: u32, reserve: u32) -> bool {
+        match max_supply {
+            Some(max_supply) => total_minted as u64 + reserve as u64 <= max_supply as u64,
+            None => true,
+        }
+    }
+
+    /// Checks that every phase ends after it starts and that phases follow each other without overlapping.
+    fn valid_schedule(phases: &[SalePhase]) -> bool {
+        phases.iter().all(|phase| phase.start < phase.end)
+            && phases.windows(2).all(|pair| pair[0].end <= pair[1].start)
+    }
+
+    fn total_weight(weights: &[(AssetId, u32)]) -> u64 {
+        weights.iter().map(|(_, weight)| *weight as u64).sum()
+    }
+
+    /// Picks the asset whose cumulative weight range contains `roll`.
+    /// `roll` has to be lower than the total weight.
+    fn pick_weighted(weights: &[(AssetId, u32)], roll: u64) -> AssetId {
+        let mut cumulative_weight = 0;
+        for (asset_id, weight) in weights {
+            cumulative_weight += *weight as u64;
+            if roll < cumulative_weight {
+                return *asset_id
+            }
+        }
+        weights[weights.len() - 1].0
+    }
+
+    /// Filters out exhausted assets from the rarity table. `exhausted` has to be sorted.
+    fn available_weights(weights: &[(AssetId, u32)], exhausted: &[AssetId]) -> Vec<(AssetId, u32)> {
+        weights
+            .iter()
+            .filter(|(asset_id, _)| exhausted.binary_search(asset_id).is_err())
+            .copied()
+            .collect()
+    }
+
+    /// Gets an asset id at `index` among ids starting from 1, skipping `exhausted` ones.
+    /// `exhausted` has to be sorted.
+    fn nth_available(exhausted: &[AssetId], index: u32) -> AssetId {
+        let mut asset_id = index + 1;
+        for exhausted_id in exhausted {
+            if *exhausted_id > asset_id {
+                break
+            }
+            asset_id += 1;
+        }
+        asset_id
+    }
+
+    /// Converts weights to probabilities in parts per million.
+    fn probabilities(weights: &[(AssetId, u32)]) -> Vec<(AssetId, u32)> {
+        let total_weight = total_weight(weights);
+        weights
+            .iter()
+            .map(|(asset_id, weight)| {
+                let probability = *weight as u64 * PROBABILITY_PRECISION / total_weight;
+                (*asset_id, probability as u32)
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{
+            BondingCurve,
+            Curve,
+        };
+        use ink::env::test;
+
+        #[ink::test]
+        fn constructor_works() {
+            let contract = init_contract();
+            assert_eq!(contract.rmrk_contract_address(), Some(rmrk_address()));
+            assert_eq!(contract.catalog_contract_address(), Some(catalog_address()));
+            assert_eq!(contract.mint_price(), 1_000_000_000_000_000_000);
+            assert_eq!(contract.max_per_tx(), DEFAULT_MAX_PER_TX);
+            assert_eq!(contract.max_per_wallet(), None);
+            assert_eq!(contract.minted_by(default_accounts().alice), 0);
+        }
+
+        #[ink::test]
+        fn set_rmrk_contract_address_works() {
+            let mut contract = init_contract();
+            let new_rmrk: AccountId = [0x43; 32].into();
+            assert!(contract.set_rmrk_contract_address(new_rmrk).is_ok());
+            assert_eq!(contract.rmrk_contract_address(), Some(new_rmrk));
+            assert!(matches!(
+                last_event(),
+                Event::RmrkContractChanged(RmrkContractChanged { contract }) if contract == new_rmrk
+            ));
+        }
+
+        #[ink::test]
+        fn set_rmrk_contract_address_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            let new_rmrk: AccountId = [0x43; 32].into();
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_rmrk_contract_address(new_rmrk),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn set_catalog_contract_address_works() {
+            let mut contract = init_contract();
+            let new_rmrk: AccountId = [0x43; 32].into();
+            assert!(contract.set_catalog_contract_address(new_rmrk).is_ok());
+            assert_eq!(contract.catalog_contract_address(), Some(new_rmrk));
+            assert!(matches!(
+                last_event(),
+                Event::CatalogContractChanged(CatalogContractChanged { contract }) if contract == new_rmrk
+            ));
+        }
+
+        #[ink::test]
+        fn set_catalog_contract_address_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            let new_rmrk: AccountId = [0x43; 32].into();
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_catalog_contract_address(new_rmrk),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn mint_fails_if_rmrk_contract_not_set() {
+            let mut contract = init_contract();
+            contract.proxy.rmrk_contract = None;
+            assert_eq!(contract.rmrk_contract_address(), None);
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint(None), Err(ProxyError::NotConfigured));
+            assert_eq!(contract.mint_many(1, None), Err(ProxyError::NotConfigured));
+            assert_eq!(
+                contract.asset_probabilities(),
+                Err(ProxyError::NotConfigured)
+            );
+        }
+
+        #[ink::test]
+        fn reveal_mint_fails_if_rmrk_contract_not_set() {
+            let mut contract = init_commit_reveal();
+            contract.proxy.rmrk_contract = None;
+            assert_eq!(
+                contract.reveal_mint(0, secret()),
+                Err(ProxyError::NotConfigured)
+            );
+        }
+
+        #[ink::test]
+        fn set_payees_works() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert_eq!(contract.payees(), vec![(accounts.alice, BASIS_POINTS)]);
+            let payees = vec![(accounts.bob, 7_000), (accounts.charlie, 3_000)];
+            assert!(contract.set_payees(payees.clone()).is_ok());
+            assert_eq!(contract.payees(), payees);
+        }
+
+        #[ink::test]
+        fn set_payees_fails_if_shares_invalid() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert_eq!(contract.set_payees(vec![]), Err(ProxyError::InvalidPayees));
+            assert_eq!(
+                contract.set_payees(vec![(accounts.bob, 7_000), (accounts.charlie, 2_000)]),
+                Err(ProxyError::InvalidPayees)
+            );
+            assert_eq!(
+                contract.set_payees(vec![(accounts.bob, BASIS_POINTS), (accounts.charlie, 0)]),
+                Err(ProxyError::InvalidPayees)
+            );
+        }
+
+        #[ink::test]
+        fn set_payees_fails_if_not_admin() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_payees(vec![(default_accounts().bob, BASIS_POINTS)]),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn split_payment_works() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract
+                .set_payees(vec![(accounts.bob, 7_000), (accounts.charlie, 3_000)])
+                .is_ok());
+            contract.split_payment(None, 1_001);
+            assert_eq!(contract.pending_payment(accounts.bob), 701);
+            assert_eq!(contract.pending_payment(accounts.charlie), 300);
+            contract.split_payment(None, 100);
+            assert_eq!(contract.pending_payment(accounts.bob), 771);
+            assert_eq!(contract.pending_payment(accounts.charlie), 330);
+        }
+
+        #[ink::test]
+        fn split_payment_does_not_overflow() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract
+                .set_payees(vec![(accounts.bob, 7_000), (accounts.charlie, 3_000)])
+                .is_ok());
+            contract.split_payment(None, Balance::MAX);
+            let charlie_share = Balance::MAX / 10 * 3 + 1;
+            assert_eq!(contract.pending_payment(accounts.charlie), charlie_share);
+            assert_eq!(
+                contract.pending_payment(accounts.bob),
+                Balance::MAX - charlie_share
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_works() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract
+                .set_payees(vec![(accounts.bob, 7_000), (accounts.charlie, 3_000)])
+                .is_ok());
+            contract.split_payment(None, 1_000);
+
+            let balance_before = balance_of(accounts.bob);
+            set_sender(accounts.charlie);
+            assert_eq!(contract.withdraw(accounts.bob), Ok(700));
+            assert_eq!(balance_of(accounts.bob), balance_before + 700);
+            assert_eq!(contract.pending_payment(accounts.bob), 0);
+            assert_eq!(contract.pending_payment(accounts.charlie), 300);
+            assert_eq!(
+                contract.withdraw(accounts.bob),
+                Err(ProxyError::NothingToWithdraw)
+            );
+        }
+
+        #[ink::test]
+        fn split_payment_credits_payment_token() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            let token = accounts.django;
+            assert!(contract
+                .set_payees(vec![(accounts.bob, 7_000), (accounts.charlie, 3_000)])
+                .is_ok());
+            contract.split_payment(Some(token), 1_001);
+            assert_eq!(contract.pending_token_payment(token, accounts.bob), 701);
+            assert_eq!(contract.pending_token_payment(token, accounts.charlie), 300);
+            assert_eq!(contract.pending_payment(accounts.bob), 0);
+            assert_eq!(
+                contract.withdraw(accounts.bob),
+                Err(ProxyError::NothingToWithdraw)
+            );
+            assert_eq!(
+                contract.withdraw_token(accounts.eve, accounts.bob),
+                Err(ProxyError::NothingToWithdraw)
+            );
+        }
+
+        #[ink::test]
+        fn set_payment_token_works() {
+            let mut contract = init_contract();
+            let token = default_accounts().django;
+            assert_eq!(contract.payment_token(), None);
+            assert!(contract.set_payment_token(Some(token)).is_ok());
+            assert_eq!(contract.payment_token(), Some(token));
+            assert!(contract.set_payment_token(None).is_ok());
+            assert_eq!(contract.payment_token(), None);
+
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_payment_token(Some(token)), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn payment_token_excludes_native_refunds() {
+            let mut contract = init_contract();
+            let token = default_accounts().django;
+            assert!(contract
+                .set_commit_reveal(Some(commit_reveal_config()))
+                .is_ok());
+            assert_eq!(
+                contract.set_payment_token(Some(token)),
+                Err(ProxyError::NativePaymentRequired)
+            );
+            assert!(contract.set_commit_reveal(None).is_ok());
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(true)))
+                .is_ok());
+            assert_eq!(
+                contract.set_payment_token(Some(token)),
+                Err(ProxyError::NativePaymentRequired)
+            );
+            assert!(contract.end_auction().is_ok());
+            assert!(contract.set_payment_token(Some(token)).is_ok());
+
+            assert_eq!(
+                contract.set_commit_reveal(Some(commit_reveal_config())),
+                Err(ProxyError::NativePaymentRequired)
+            );
+            assert_eq!(
+                contract.set_pricing_mode(PricingMode::DutchAuction(dutch_auction(true))),
+                Err(ProxyError::NativePaymentRequired)
+            );
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(false)))
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn mint_fails_if_native_value_sent_with_payment_token() {
+            let mut contract = init_contract();
+            assert!(contract
+                .set_payment_token(Some(default_accounts().django))
+                .is_ok());
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint(None), Err(ProxyError::BadMintValue));
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let mut contract = init_contract();
+            assert!(!contract.is_paused());
+            assert!(contract.pause().is_ok());
+            assert!(contract.is_paused());
+            assert!(matches!(
+                last_event(),
+                Event::Paused(Paused { account }) if account == default_accounts().alice
+            ));
+            assert_eq!(contract.pause(), Err(ProxyError::Paused));
+
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint(None), Err(ProxyError::Paused));
+            assert_eq!(contract.mint_many(1, None), Err(ProxyError::Paused));
+            assert_eq!(
+                contract.commit_mint(Hash::from([0x01; 32]), None),
+                Err(ProxyError::Paused)
+            );
+            // Reveals don't wait for the pause to end, which could let reservations expire.
+            assert_eq!(
+                contract.reveal_mint(0, Hash::from([0x01; 32])),
+                Err(ProxyError::CommitRevealDisabled)
+            );
+
+            assert!(contract.unpause().is_ok());
+            assert!(!contract.is_paused());
+            assert!(matches!(last_event(), Event::Unpaused(_)));
+            assert_eq!(contract.unpause(), Err(ProxyError::NotPaused));
+        }
+
+        #[ink::test]
+        fn pauser_can_only_pause() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.grant_role(PAUSER, accounts.bob).is_ok());
+
+            set_sender(accounts.bob);
+            assert!(contract.pause().is_ok());
+            assert_eq!(contract.unpause(), Err(missing_role()));
+            assert_eq!(contract.set_mint_price(100), Err(missing_role()));
+            assert_eq!(
+                contract.set_rmrk_contract_address(accounts.bob),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn pause_fails_if_not_pauser() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.pause(), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn unpause_fails_if_not_admin() {
+            let mut contract = init_contract();
+            assert!(contract.pause().is_ok());
+            assert!(contract
+                .renounce_role(ADMIN, default_accounts().alice)
+                .is_ok());
+            assert_eq!(contract.unpause(), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn constructor_grants_all_roles_to_deployer() {
+            let contract = init_contract();
+            let accounts = default_accounts();
+            for role in [ADMIN, PRICE_MANAGER, CONFIG_MANAGER, PAUSER, RESERVE_MINTER] {
+                assert!(contract.has_role(role, accounts.alice));
+                assert!(!contract.has_role(role, accounts.bob));
+            }
+        }
+
+        #[ink::test]
+        fn roles_are_separated() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.grant_role(PRICE_MANAGER, accounts.bob).is_ok());
+            assert!(contract
+                .grant_role(CONFIG_MANAGER, accounts.charlie)
+                .is_ok());
+
+            set_sender(accounts.bob);
+            assert!(contract.set_mint_price(100).is_ok());
+            assert_eq!(contract.set_max_per_tx(5), Err(missing_role()));
+            assert_eq!(
+                contract.grant_role(PRICE_MANAGER, accounts.django),
+                Err(AccessControlError::MissingRole)
+            );
+
+            set_sender(accounts.charlie);
+            assert!(contract.set_max_per_tx(5).is_ok());
+            assert_eq!(contract.set_mint_price(200), Err(missing_role()));
+            assert_eq!(contract.mint_price(), 100);
+        }
+
+        #[ink::test]
+        fn mint_to_checks_recipient_allowlist() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            init_presale(&mut contract, accounts.bob, 2);
+            let proof = AllowlistProof {
+                quota: 2,
+                proof: Vec::new(),
+            };
+
+            set_sender(accounts.bob);
+            set_value(PRESALE_PRICE);
+            assert_eq!(
+                contract.mint_to(accounts.charlie, Some(proof.clone())),
+                Err(ProxyError::NotAllowlisted)
+            );
+            set_value(PRESALE_PRICE * 3);
+            set_sender(accounts.charlie);
+            assert_eq!(
+                contract.mint_many_to(accounts.bob, 3, Some(proof)),
+                Err(ProxyError::AllowlistQuotaExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn mint_to_counts_wallet_limit_against_recipient() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.set_max_per_wallet(Some(1)).is_ok());
+            contract
+                .proxy
+                .phase_minted
+                .insert(&(None, accounts.bob), &1);
+
+            set_sender(accounts.charlie);
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(
+                contract.mint_to(accounts.bob, None),
+                Err(ProxyError::PerWalletLimitExceeded)
+            );
+            set_value(2_000_000_000_000_000_000);
+            assert_eq!(
+                contract.mint_many_to(accounts.bob, 2, None),
+                Err(ProxyError::PerWalletLimitExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn mint_to_fails_if_bad_value() {
+            let mut contract = init_contract();
+            set_value(1);
+            assert_eq!(
+                contract.mint_to(default_accounts().bob, None),
+                Err(ProxyError::BadMintValue)
+            );
+            assert_eq!(
+                contract.mint_many_to(default_accounts().bob, 0, None),
+                Err(ProxyError::BadMintCount)
+            );
+        }
+
+        #[ink::test]
+        fn set_mint_price_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_mint_price(100).is_ok());
+            assert_eq!(contract.mint_price(), 100);
+            assert!(matches!(
+                last_event(),
+                Event::MintPriceChanged(MintPriceChanged { price: 100 })
+            ));
+        }
+
+        #[ink::test]
+        fn set_mint_price_fails_if_not_price_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_mint_price(100), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn set_max_per_tx_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_max_per_tx(3).is_ok());
+            assert_eq!(contract.max_per_tx(), 3);
+            assert_eq!(contract.set_max_per_tx(0), Err(ProxyError::BadMintCount));
+        }
+
+        #[ink::test]
+        fn set_max_per_tx_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_max_per_tx(3), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn set_max_per_wallet_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_max_per_wallet(Some(2)).is_ok());
+            assert_eq!(contract.max_per_wallet(), Some(2));
+        }
+
+        #[ink::test]
+        fn set_max_per_wallet_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_max_per_wallet(Some(2)), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn set_presale_price_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_presale_price(Some(100)).is_ok());
+            assert_eq!(contract.presale_price(), Some(100));
+        }
+
+        #[ink::test]
+        fn set_allowlist_root_works() {
+            let mut contract = init_contract();
+            let root = Hash::from([0x01; 32]);
+            assert!(contract.set_allowlist_root(Some(root)).is_ok());
+            assert_eq!(contract.allowlist_root(), Some(root));
+        }
+
+        #[ink::test]
+        fn set_allowlist_root_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_allowlist_root(Some(Hash::from([0x01; 32]))),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn set_asset_weight_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_weight(1, 10).is_ok());
+            assert!(contract.set_asset_weight(2, 30).is_ok());
+            assert!(contract.set_asset_weight(3, 0).is_ok());
+            assert_eq!(contract.asset_weights(), vec![(1, 10), (2, 30)]);
+
+            assert!(contract.set_asset_weight(1, 20).is_ok());
+            assert!(contract.set_asset_weight(2, 0).is_ok());
+            assert_eq!(contract.asset_weights(), vec![(1, 20)]);
+        }
+
+        #[ink::test]
+        fn set_asset_weight_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_asset_weight(1, 10), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn asset_probabilities_follow_weights() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_weight(1, 1).is_ok());
+            assert!(contract.set_asset_weight(2, 3).is_ok());
+            assert!(contract.set_asset_weight(3, 4).is_ok());
+            assert_eq!(
+                contract.asset_probabilities(),
+                Ok(vec![(1, 125_000), (2, 375_000), (3, 500_000)])
+            );
+        }
+
+        #[ink::test]
+        fn pick_weighted_uses_cumulative_weights() {
+            let weights = [(1, 1), (2, 3), (5, 4)];
+            assert_eq!(pick_weighted(&weights, 0), 1);
+            assert_eq!(pick_weighted(&weights, 1), 2);
+            assert_eq!(pick_weighted(&weights, 3), 2);
+            assert_eq!(pick_weighted(&weights, 4), 5);
+            assert_eq!(pick_weighted(&weights, 7), 5);
+        }
+
+        #[ink::test]
+        fn pick_asset_uses_rarity_table() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_weight(7, 1).is_ok());
+            for _ in 0..10 {
+                assert_eq!(contract.pick_asset(10, &mut pseudo_random()), Ok(7));
+            }
+        }
+
+        #[ink::test]
+        fn pick_asset_fails_if_weighted_asset_not_found() {
+            let mut contract = init_contract();
+            assert_eq!(
+                contract.set_asset_weight(0, 1),
+                Err(ProxyError::AssetNotFound)
+            );
+            assert!(contract.set_asset_weight(1, 1).is_ok());
+            assert!(contract.set_asset_weight(11, 1).is_ok());
+            assert_eq!(
+                contract.pick_asset(10, &mut pseudo_random()),
+                Err(ProxyError::AssetNotFound)
+            );
+            assert!(contract.pick_asset(11, &mut pseudo_random()).is_ok());
+        }
+
+        #[ink::test]
+        fn set_asset_supply_works() {
+            let mut contract = init_contract();
+            assert_eq!(contract.asset_supply(1), None);
+            assert!(contract.set_asset_supply(1, Some(5)).is_ok());
+            assert_eq!(contract.asset_supply(1), Some(5));
+            assert!(contract.set_asset_supply(1, None).is_ok());
+            assert_eq!(contract.asset_supply(1), None);
+        }
+
+        #[ink::test]
+        fn set_asset_supply_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_asset_supply(1, Some(5)), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn pick_asset_decrements_supply() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_supply(1, Some(2)).is_ok());
+            assert!(contract.set_asset_supply(2, Some(0)).is_ok());
+
+            assert_eq!(contract.pick_asset(2, &mut pseudo_random()), Ok(1));
+            assert_eq!(contract.asset_supply(1), Some(1));
+            assert_eq!(contract.pick_asset(2, &mut pseudo_random()), Ok(1));
+            assert_eq!(contract.asset_supply(1), Some(0));
+            assert_eq!(
+                contract.pick_asset(2, &mut pseudo_random()),
+                Err(ProxyError::SoldOut)
+            );
+        }
+
+        #[ink::test]
+        fn pick_asset_skips_exhausted_weighted_assets() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_weight(1, 1000).is_ok());
+            assert!(contract.set_asset_weight(2, 1).is_ok());
+            assert!(contract.set_asset_supply(1, Some(0)).is_ok());
+            assert!(contract.set_asset_supply(2, Some(1)).is_ok());
+
+            assert_eq!(contract.asset_probabilities(), Ok(vec![(2, 1_000_000)]));
+            assert_eq!(contract.pick_asset(2, &mut pseudo_random()), Ok(2));
+            assert_eq!(
+                contract.pick_asset(2, &mut pseudo_random()),
+                Err(ProxyError::SoldOut)
+            );
+            assert_eq!(contract.asset_probabilities(), Err(ProxyError::SoldOut));
+        }
+
+        #[ink::test]
+        fn restocked_asset_is_available_again() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_supply(1, Some(0)).is_ok());
+            assert_eq!(
+                contract.pick_asset(1, &mut pseudo_random()),
+                Err(ProxyError::SoldOut)
+            );
+            assert!(contract.set_asset_supply(1, Some(1)).is_ok());
+            assert_eq!(contract.pick_asset(1, &mut pseudo_random()), Ok(1));
+        }
+
+        #[ink::test]
+        fn get_random_stays_in_bounds() {
+            let mut contract = init_contract();
+            let mut seen = [false; 3];
+            for _ in 0..100 {
+                let value = contract.get_random(3, &mut pseudo_random()).unwrap();
+                assert!(value < 3);
+                seen[value as usize] = true;
+            }
+            assert_eq!(seen, [true; 3]);
+            assert_eq!(contract.get_random(1, &mut pseudo_random()), Ok(0));
+            assert!(contract.get_random(u64::MAX, &mut pseudo_random()).unwrap() < u64::MAX);
+        }
+
+        #[ink::test]
+        fn get_random_rejects_biased_draws() {
+            let mut contract = init_contract();
+            // 2^64 % 3 == 1, so u64::MAX is the only biased draw.
+            let mut randomness = MockRandomness::new(&[seed(&[u64::MAX, 5])]);
+            assert_eq!(contract.get_random(3, &mut randomness), Ok(2));
+        }
+
+        #[ink::test]
+        fn pick_asset_uses_randomness_source() {
+            let mut contract = init_contract();
+            let mut randomness = MockRandomness::new(&[seed(&[3]), seed(&[10]), seed(&[9])]);
+            assert_eq!(contract.pick_asset(10, &mut randomness), Ok(4));
+            assert_eq!(contract.pick_asset(10, &mut randomness), Ok(1));
+            assert_eq!(contract.pick_asset(10, &mut randomness), Ok(10));
+            assert_eq!(
+                randomness.subjects,
+                [0u64, 1, 2].map(|salt| salt.to_be_bytes().to_vec())
+            );
+
+            assert!(contract.set_asset_weight(1, 1).is_ok());
+            assert!(contract.set_asset_weight(2, 3).is_ok());
+            let mut randomness = MockRandomness::new(&[seed(&[0]), seed(&[1]), seed(&[7])]);
+            assert_eq!(contract.pick_asset(10, &mut randomness), Ok(1));
+            assert_eq!(contract.pick_asset(10, &mut randomness), Ok(2));
+            assert_eq!(contract.pick_asset(10, &mut randomness), Ok(2));
+        }
+
+        #[ink::test]
+        fn set_randomness_works() {
+            let mut contract = init_contract();
+            assert_eq!(contract.randomness(), RandomnessConfig::PseudoRandom);
+            let oracle = RandomnessConfig::Oracle {
+                contract: [0x44; 32].into(),
+                selector: [0x01, 0x02, 0x03, 0x04],
+            };
+            assert!(contract.set_randomness(oracle.clone()).is_ok());
+            assert_eq!(contract.randomness(), oracle);
+        }
+
+        #[ink::test]
+        fn set_randomness_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_randomness(RandomnessConfig::PseudoRandom),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn new_with_randomness_works() {
+            let oracle = RandomnessConfig::Oracle {
+                contract: [0x44; 32].into(),
+                selector: [0x01, 0x02, 0x03, 0x04],
+            };
+            let contract = RmrkProxy::new_with_randomness(
+                rmrk_address(),
+                catalog_address(),
+                1_000_000_000_000_000_000,
+                oracle.clone(),
+            );
+            assert_eq!(contract.randomness(), oracle);
+        }
+
+        #[ink::test]
+        fn pick_asset_works_with_many_assets() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_supply(1_000, Some(0)).is_ok());
+            for _ in 0..100 {
+                let asset_id = contract.pick_asset(100_000, &mut pseudo_random()).unwrap();
+                assert!((1..=100_000).contains(&asset_id));
+                assert_ne!(asset_id, 1_000);
+            }
+        }
+
+        #[ink::test]
+        fn nth_available_skips_exhausted_assets() {
+            assert_eq!(nth_available(&[], 0), 1);
+            assert_eq!(nth_available(&[1, 3], 0), 2);
+            assert_eq!(nth_available(&[1, 3], 1), 4);
+            assert_eq!(nth_available(&[2, 3, 4], 1), 5);
+            assert_eq!(nth_available(&[5], 3), 4);
+        }
+
+        #[ink::test]
+        fn set_commit_reveal_works() {
+            let mut contract = init_contract();
+            assert_eq!(contract.commit_reveal(), None);
+            assert!(contract
+                .set_commit_reveal(Some(commit_reveal_config()))
+                .is_ok());
+            assert_eq!(contract.commit_reveal(), Some(commit_reveal_config()));
+            assert_eq!(
+                contract.set_commit_reveal(Some(CommitRevealConfig {
+                    reveal_delay: 0,
+                    reveal_window: 10,
+                })),
+                Err(ProxyError::InvalidCommitRevealConfig)
+            );
+        }
+
+        #[ink::test]
+        fn set_expiry_fee_works() {
+            let mut contract = init_contract();
+            assert_eq!(contract.expiry_fee(), DEFAULT_EXPIRY_FEE);
+            assert!(contract.set_expiry_fee(BASIS_POINTS).is_ok());
+            assert_eq!(contract.expiry_fee(), BASIS_POINTS);
+            assert_eq!(
+                contract.set_expiry_fee(BASIS_POINTS + 1),
+                Err(ProxyError::InvalidCommitRevealConfig)
+            );
+
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_expiry_fee(0), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn refund_reservation_is_full_once_commit_reveal_disabled() {
+            let mut contract = init_commit_reveal();
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &bob), None)
+                .is_ok());
+
+            set_sender(default_accounts().alice);
+            assert!(contract.set_commit_reveal(None).is_ok());
+            set_sender(bob);
+            assert_eq!(contract.refund_reservation(0), Ok(COMMIT_PRICE));
+        }
+
+        #[ink::test]
+        fn set_commit_reveal_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_commit_reveal(Some(commit_reveal_config())),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn mint_fails_if_commit_reveal_enabled() {
+            let mut contract = init_contract();
+            assert!(contract
+                .set_commit_reveal(Some(commit_reveal_config()))
+                .is_ok());
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint(None), Err(ProxyError::CommitRevealRequired));
+            assert_eq!(
+                contract.mint_many(1, None),
+                Err(ProxyError::CommitRevealRequired)
+            );
+        }
+
+        #[ink::test]
+        fn commit_mint_fails_if_commit_reveal_disabled() {
+            let mut contract = init_contract();
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(
+                contract.commit_mint(Hash::from([0x01; 32]), None),
+                Err(ProxyError::CommitRevealDisabled)
+            );
+        }
+
+        #[ink::test]
+        fn commit_mint_works() {
+            let mut contract = init_commit_reveal();
+            let bob = default_accounts().bob;
+            let commitment = commitment_hash(&secret(), &bob);
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert_eq!(contract.commit_reservation(commitment, None), Ok(0));
+            assert_eq!(
+                contract.lock_rmrk_price(0, COMMIT_PRICE + 1),
+                Err(ProxyError::RmrkPriceNotCovered)
+            );
+            assert_eq!(contract.lock_rmrk_price(0, 40), Ok(()));
+            assert_eq!(contract.commit_reservation(commitment, None), Ok(1));
+            assert_eq!(
+                contract.reservation(0),
+                Some(Reservation {
+                    owner: bob,
+                    commitment,
+                    committed_at: 0,
+                    paid: COMMIT_PRICE,
+                    phase: None,
+                })
+            );
+            assert_eq!(contract.proxy.reservation_rmrk_prices.get(&0), Some(40));
+            assert_eq!(contract.minted_by(bob), 2);
+        }
+
+        #[ink::test]
+        fn reveal_mint_fails_if_too_early_or_expired() {
+            let mut contract = init_commit_reveal();
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &bob), None)
+                .is_ok());
+
+            assert_eq!(
+                contract.reveal_mint(0, secret()),
+                Err(ProxyError::RevealTooEarly)
+            );
+            advance_blocks(commit_reveal_config().reveal_delay);
+            assert_eq!(
+                contract.reveal_mint(0, Hash::from([0x03; 32])),
+                Err(ProxyError::InvalidSecret)
+            );
+            advance_blocks(commit_reveal_config().reveal_window);
+            assert_eq!(
+                contract.reveal_mint(0, secret()),
+                Err(ProxyError::ReservationExpired)
+            );
+        }
+
+        #[ink::test]
+        fn reveal_outcome_is_fixed_across_reveal_window() {
+            let mut contract = init_commit_reveal();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            set_value(COMMIT_PRICE);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &accounts.bob), None)
+                .is_ok());
+            let reservation = contract.reservation(0).unwrap();
+            let (_, pool_at_commit) = contract.proxy.entropy.get().unwrap();
+            let reveal_from = commit_reveal_config().reveal_delay;
+
+            // A later commit is mixed into the entropy the reservation is revealed with.
+            advance_blocks(1);
+            set_sender(accounts.charlie);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &accounts.charlie), None)
+                .is_ok());
+            advance_blocks(reveal_from - 1);
+
+            // Reveals and draws in between change the pool and the salt, but not the outcome.
+            let mut revealed = Vec::new();
+            for _ in 0..commit_reveal_config().reveal_window {
+                let mut randomness = contract
+                    .reveal_randomness(0, &reservation, secret(), reveal_from)
+                    .unwrap();
+                revealed.push(contract.pick_asset(1_000, &mut randomness).unwrap());
+                assert!(contract.pick_asset(1_000, &mut pseudo_random()).is_ok());
+                advance_blocks(1);
+            }
+            assert!(revealed.iter().all(|asset_id| *asset_id == revealed[0]));
+            assert_ne!(contract.entropy_at(0, reveal_from), Some(pool_at_commit));
+            assert_eq!(
+                contract.entropy_at(0, reveal_from),
+                contract.entropy_at(1, reveal_from)
+            );
+        }
+
+        #[ink::test]
+        fn reveal_mint_fails_if_not_reservation_owner() {
+            let mut contract = init_commit_reveal();
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &bob), None)
+                .is_ok());
+
+            advance_blocks(commit_reveal_config().reveal_delay);
+            set_sender(default_accounts().charlie);
+            assert_eq!(
+                contract.reveal_mint(0, secret()),
+                Err(ProxyError::NotReservationOwner)
+            );
+            assert_eq!(
+                contract.reveal_mint(1, secret()),
+                Err(ProxyError::ReservationNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn refund_reservation_works_after_expiry() {
+            let mut contract = init_commit_reveal();
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &bob), None)
+                .is_ok());
+            assert_eq!(
+                contract.refund_reservation(0),
+                Err(ProxyError::ReservationNotExpired)
+            );
+
+            let config = commit_reveal_config();
+            advance_blocks(config.reveal_delay + config.reveal_window);
+            let balance_before = balance_of(bob);
+            assert_eq!(contract.refund_reservation(0), Ok(COMMIT_PRICE / 2));
+            assert_eq!(balance_of(bob), balance_before + COMMIT_PRICE / 2);
+            assert_eq!(
+                contract.pending_payment(default_accounts().alice),
+                COMMIT_PRICE / 2
+            );
+            assert_eq!(contract.reservation(0), None);
+            assert_eq!(contract.proxy.reservation_rmrk_prices.get(&0), None);
+            assert_eq!(contract.minted_by(bob), 0);
+            assert_eq!(
+                contract.refund_reservation(0),
+                Err(ProxyError::ReservationNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn add_phase_works() {
+            let mut contract = init_contract();
+            assert_eq!(contract.add_phase(phase(100, 200)), Ok(0));
+            assert_eq!(contract.add_phase(phase(200, 300)), Ok(1));
+            assert_eq!(contract.phases(), vec![phase(100, 200), phase(200, 300)]);
+        }
+
+        #[ink::test]
+        fn add_phase_fails_if_invalid() {
+            let mut contract = init_contract();
+            assert_eq!(
+                contract.add_phase(phase(200, 200)),
+                Err(ProxyError::InvalidPhase)
+            );
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            assert_eq!(
+                contract.add_phase(phase(150, 300)),
+                Err(ProxyError::InvalidPhase)
+            );
+            set_timestamp(500);
+            assert_eq!(
+                contract.add_phase(phase(400, 600)),
+                Err(ProxyError::PhaseAlreadyStarted)
+            );
+        }
+
+        #[ink::test]
+        fn add_phase_fails_if_not_config_manager() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.add_phase(phase(100, 200)), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn add_phase_fails_if_not_price_manager() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.grant_role(CONFIG_MANAGER, accounts.bob).is_ok());
+            set_sender(accounts.bob);
+            assert_eq!(contract.add_phase(phase(100, 200)), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn phase_edits_are_split_between_roles() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            assert!(contract.grant_role(PRICE_MANAGER, accounts.bob).is_ok());
+            assert!(contract
+                .grant_role(CONFIG_MANAGER, accounts.charlie)
+                .is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.update_phase(0, phase(150, 250)),
+                Err(missing_role())
+            );
+            assert_eq!(contract.remove_phase(0), Err(missing_role()));
+            assert!(contract.set_phase_price(0, PHASE_PRICE * 2).is_ok());
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                contract.set_phase_price(0, PHASE_PRICE),
+                Err(missing_role())
+            );
+            let cheaper = SalePhase {
+                price: PHASE_PRICE,
+                ..phase(100, 200)
+            };
+            assert_eq!(contract.update_phase(0, cheaper), Err(missing_role()));
+            let later = SalePhase {
+                price: PHASE_PRICE * 2,
+                ..phase(150, 250)
+            };
+            assert!(contract.update_phase(0, later.clone()).is_ok());
+            assert_eq!(contract.phases(), vec![later]);
+            assert!(contract.remove_phase(0).is_ok());
+        }
+
+        #[ink::test]
+        fn set_phase_price_fails_if_started() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            assert_eq!(
+                contract.set_phase_price(1, 1),
+                Err(ProxyError::PhaseNotFound)
+            );
+            set_timestamp(100);
+            assert_eq!(
+                contract.set_phase_price(0, 1),
+                Err(ProxyError::PhaseAlreadyStarted)
+            );
+        }
+
+        #[ink::test]
+        fn update_phase_works() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            assert!(contract.update_phase(0, phase(150, 250)).is_ok());
+            assert_eq!(contract.phases(), vec![phase(150, 250)]);
+            assert_eq!(
+                contract.update_phase(1, phase(300, 400)),
+                Err(ProxyError::PhaseNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn update_phase_fails_if_started() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            set_timestamp(100);
+            assert_eq!(
+                contract.update_phase(0, phase(300, 400)),
+                Err(ProxyError::PhaseAlreadyStarted)
+            );
+            assert_eq!(
+                contract.remove_phase(0),
+                Err(ProxyError::PhaseAlreadyStarted)
+            );
+        }
+
+        #[ink::test]
+        fn remove_phase_works() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            assert!(contract.add_phase(phase(200, 300)).is_ok());
+            assert!(contract.remove_phase(0).is_ok());
+            assert_eq!(contract.phases(), vec![phase(200, 300)]);
+        }
+
+        #[ink::test]
+        fn sale_status_follows_phases() {
+            let mut contract = init_contract();
+            assert_eq!(contract.sale_status(), SaleStatus::Public);
+            let presale = SalePhase {
+                allowlist_root: Some(Hash::from([0x01; 32])),
+                ..phase(100, 200)
+            };
+            assert!(contract.add_phase(presale).is_ok());
+            assert!(contract.add_phase(phase(300, 400)).is_ok());
+
+            assert_eq!(contract.sale_status(), SaleStatus::Closed);
+            set_timestamp(100);
+            assert_eq!(contract.sale_status(), SaleStatus::Presale);
+            assert_eq!(contract.active_phase(), Some(0));
+            set_timestamp(200);
+            assert_eq!(contract.sale_status(), SaleStatus::Closed);
+            assert_eq!(contract.active_phase(), None);
+            set_timestamp(399);
+            assert_eq!(contract.sale_status(), SaleStatus::Public);
+            assert_eq!(contract.active_phase(), Some(1));
+            set_timestamp(400);
+            assert_eq!(contract.sale_status(), SaleStatus::Ended);
+        }
+
+        #[ink::test]
+        fn mint_fails_if_sale_not_active() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            set_value(PHASE_PRICE);
+            assert_eq!(contract.mint(None), Err(ProxyError::SaleNotActive));
+            set_timestamp(200);
+            assert_eq!(contract.mint(None), Err(ProxyError::SaleNotActive));
+        }
+
+        #[ink::test]
+        fn mint_fails_if_not_phase_price() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            set_timestamp(100);
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint(None), Err(ProxyError::BadMintValue));
+        }
+
+        #[ink::test]
+        fn mint_many_fails_if_phase_wallet_limit_exceeded() {
+            let mut contract = init_contract();
+            let limited = SalePhase {
+                max_per_wallet: Some(1),
+                ..phase(100, 200)
+            };
+            assert!(contract.add_phase(limited).is_ok());
+            set_timestamp(100);
+            set_value(2 * PHASE_PRICE);
+            assert_eq!(
+                contract.mint_many(2, None),
+                Err(ProxyError::PerWalletLimitExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn mint_many_fails_if_wallet_limit_exceeded() {
+            let mut contract = init_contract();
+            assert!(contract.set_max_per_wallet(Some(1)).is_ok());
+            set_value(2_000_000_000_000_000_000);
+            assert_eq!(
+                contract.mint_many(2, None),
+                Err(ProxyError::PerWalletLimitExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn phase_wallet_limit_overrides_global_limit() {
+            let mut contract = init_contract();
+            assert!(contract.set_max_per_wallet(Some(1)).is_ok());
+            let limited = SalePhase {
+                max_per_wallet: Some(3),
+                ..phase(100, 200)
+            };
+            assert!(contract.add_phase(limited).is_ok());
+            assert!(contract.add_phase(phase(200, 300)).is_ok());
+
+            set_timestamp(100);
+            set_value(4 * PHASE_PRICE);
+            assert_eq!(
+                contract.mint_many(4, None),
+                Err(ProxyError::PerWalletLimitExceeded)
+            );
+            set_timestamp(200);
+            set_value(2 * PHASE_PRICE);
+            assert_eq!(
+                contract.mint_many(2, None),
+                Err(ProxyError::PerWalletLimitExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn mint_fails_if_not_allowlisted_in_phase() {
+            let mut contract = init_contract();
+            let presale = SalePhase {
+                allowlist_root: Some(merkle::allowlist_leaf(&default_accounts().alice, 1)),
+                ..phase(100, 200)
+            };
+            assert!(contract.add_phase(presale).is_ok());
+            set_timestamp(100);
+            set_sender(default_accounts().bob);
+            set_value(PHASE_PRICE);
+            assert_eq!(contract.mint(None), Err(ProxyError::NotAllowlisted));
+        }
+
+        #[ink::test]
+        fn mint_fails_if_no_balance() {
+            let mut contract = init_contract();
+            let mint_result = contract.mint(None);
+            assert_eq!(mint_result, Err(ProxyError::BadMintValue));
+        }
+
+        #[ink::test]
+        fn mint_fails_if_not_allowlisted() {
+            let mut contract = init_contract();
+            init_presale(&mut contract, default_accounts().alice, 1);
+            set_sender(default_accounts().bob);
+            set_value(PRESALE_PRICE);
+            assert_eq!(contract.mint(None), Err(ProxyError::NotAllowlisted));
+            assert_eq!(
+                contract.mint(Some(AllowlistProof {
+                    quota: 1,
+                    proof: Vec::new(),
+                })),
+                Err(ProxyError::NotAllowlisted)
+            );
+        }
+
+        #[ink::test]
+        fn mint_fails_if_not_presale_price() {
+            let mut contract = init_contract();
+            init_presale(&mut contract, default_accounts().alice, 1);
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(
+                contract.mint(Some(AllowlistProof {
+                    quota: 1,
+                    proof: Vec::new(),
+                })),
+                Err(ProxyError::BadMintValue)
+            );
+        }
+
+        #[ink::test]
+        fn presale_mints_are_counted_apart_from_public_mints() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.set_max_per_wallet(Some(1)).is_ok());
+            contract
+                .proxy
+                .phase_minted
+                .insert(&(None, accounts.alice), &1);
+            init_presale(&mut contract, accounts.alice, 1);
+
+            let terms = contract.sale_terms().unwrap();
+            assert_eq!(terms.phase, Some(PRESALE_PHASE));
+            let allowlist = AllowlistProof {
+                quota: 1,
+                proof: Vec::new(),
+            };
+            assert!(contract
+                .consume_allowance(accounts.alice, 1, &terms, Some(allowlist))
+                .is_ok());
+            assert_eq!(
+                contract.phase_minted(Some(PRESALE_PHASE), accounts.alice),
+                1
+            );
+            assert_eq!(contract.phase_minted(None, accounts.alice), 1);
+            assert_eq!(contract.minted_by(accounts.alice), 1);
+        }
+
+        #[ink::test]
+        fn mint_many_fails_if_allowlist_quota_exceeded() {
+            let mut contract = init_contract();
+            init_presale(&mut contract, default_accounts().alice, 1);
+            set_value(2 * PRESALE_PRICE);
+            assert_eq!(
+                contract.mint_many(
+                    2,
+                    Some(AllowlistProof {
+                        quota: 1,
+                        proof: Vec::new(),
+                    })
+                ),
+                Err(ProxyError::AllowlistQuotaExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn mint_many_fails_if_count_is_zero() {
+            let mut contract = init_contract();
+            assert_eq!(contract.mint_many(0, None), Err(ProxyError::BadMintCount));
+        }
+
+        #[ink::test]
+        fn mint_many_fails_if_over_per_tx_limit() {
+            let mut contract = init_contract();
+            assert_eq!(
+                contract.mint_many(DEFAULT_MAX_PER_TX + 1, None),
+                Err(ProxyError::PerTxLimitExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn mint_many_fails_if_bad_value() {
+            let mut contract = init_contract();
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint_many(2, None), Err(ProxyError::BadMintValue));
+        }
+
+        #[ink::test]
+        fn upgrade_fails_without_role() {
+            let mut contract = init_contract();
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.upgrade([0x01; 32]), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn migrate_fails_if_up_to_date_or_not_admin() {
+            let mut contract = init_contract();
+            assert_eq!(contract.storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.migrate(), Err(ProxyError::NothingToMigrate));
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.migrate(), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn from_v0_keeps_legacy_data() {
+            let accounts = default_accounts();
+            let legacy = DataV0 {
+                rmrk_contract: Some(rmrk_address()),
+                catalog_contract: Some(catalog_address()),
+                mint_price: 5,
+                salt: 7,
+            };
+            let contract = RmrkProxy::from_v0(accounts.bob, &legacy);
+            assert_eq!(contract.storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.rmrk_contract_address(), Some(rmrk_address()));
+            assert_eq!(contract.catalog_contract_address(), Some(catalog_address()));
+            assert_eq!(contract.mint_price(), 5);
+            assert_eq!(contract.proxy.salt, 7);
+            assert_eq!(contract.max_per_tx(), DEFAULT_MAX_PER_TX);
+            assert_eq!(contract.payees(), vec![(accounts.bob, BASIS_POINTS)]);
+            for role in [ADMIN, PRICE_MANAGER, CONFIG_MANAGER, PAUSER, RESERVE_MINTER] {
+                assert!(contract.has_role(role, accounts.bob));
+                assert!(!contract.has_role(role, accounts.alice));
+            }
+        }
+
+        #[ink::test]
+        fn set_reserve_and_max_supply_work() {
+            let mut contract = init_contract();
+            assert_eq!(contract.max_supply(), None);
+            assert!(contract.set_reserve(20).is_ok());
+            assert!(contract.set_max_supply(Some(20)).is_ok());
+            assert_eq!(contract.reserve_remaining(), 20);
+            assert_eq!(contract.max_supply(), Some(20));
+
+            assert_eq!(contract.set_reserve(21), Err(ProxyError::InvalidSupply));
+            assert_eq!(
+                contract.set_max_supply(Some(19)),
+                Err(ProxyError::InvalidSupply)
+            );
+
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.set_reserve(1), Err(missing_role()));
+            assert_eq!(contract.set_max_supply(None), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn reserve_mint_fails_without_role() {
+            let mut contract = init_contract();
+            assert!(contract.set_reserve(1).is_ok());
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            assert_eq!(
+                contract.reserve_mint(vec![(bob, 1, None)]),
+                Err(missing_role())
+            );
+        }
+
+        #[ink::test]
+        fn reserve_mint_fails_if_reserve_exceeded() {
+            let mut contract = init_contract();
+            assert!(contract.set_reserve(2).is_ok());
+            let accounts = default_accounts();
+            assert_eq!(
+                contract.reserve_mint(vec![
+                    (accounts.bob, 2, None),
+                    (accounts.charlie, 1, Some(1))
+                ]),
+                Err(ProxyError::ReserveExceeded)
+            );
+            assert_eq!(
+                contract.reserve_mint(vec![
+                    (accounts.bob, u32::MAX, None),
+                    (accounts.charlie, 1, None)
+                ]),
+                Err(ProxyError::ReserveExceeded)
+            );
+            assert_eq!(
+                contract.reserve_mint(Vec::new()),
+                Err(ProxyError::BadMintCount)
+            );
+            assert_eq!(contract.reserve_remaining(), 2);
+        }
+
+        #[ink::test]
+        fn mint_fails_if_only_reserve_left() {
+            let mut contract = init_contract();
+            assert!(contract.set_reserve(2).is_ok());
+            assert!(contract.set_max_supply(Some(2)).is_ok());
+            set_value(1_000_000_000_000_000_000);
+            assert_eq!(contract.mint(None), Err(ProxyError::SoldOut));
+            assert_eq!(contract.total_minted(), 0);
+        }
+
+        #[ink::test]
+        fn commit_mint_counts_towards_max_supply() {
+            let mut contract = init_commit_reveal();
+            assert!(contract.set_reserve(1).is_ok());
+            assert!(contract.set_max_supply(Some(2)).is_ok());
+            let bob = default_accounts().bob;
+            let commitment = commitment_hash(&secret(), &bob);
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert_eq!(contract.commit_reservation(commitment, None), Ok(0));
+            assert_eq!(
+                contract.commit_reservation(commitment, None),
+                Err(ProxyError::SoldOut)
+            );
+            assert_eq!(contract.total_minted(), 1);
+
+            let config = commit_reveal_config();
+            advance_blocks(config.reveal_delay + config.reveal_window);
+            assert!(contract.refund_reservation(0).is_ok());
+            assert_eq!(contract.total_minted(), 0);
+        }
+
+        #[ink::test]
+        fn take_asset_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_asset_supply(2, Some(1)).is_ok());
+            assert_eq!(contract.take_asset(2, 3), Ok(2));
+            assert_eq!(contract.asset_supply(2), Some(0));
+            assert_eq!(contract.take_asset(2, 3), Err(ProxyError::SoldOut));
+            assert_eq!(contract.take_asset(3, 3), Ok(3));
+            assert_eq!(contract.take_asset(0, 3), Err(ProxyError::AssetNotFound));
+            assert_eq!(contract.take_asset(4, 3), Err(ProxyError::AssetNotFound));
+        }
+
+        #[ink::test]
+        fn timelocked_change_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            assert_eq!(
+                contract.set_mint_price(5),
+                Err(ProxyError::TimelockRequired)
+            );
+            let new_rmrk: AccountId = [0x43; 32].into();
+            assert_eq!(
+                contract.set_rmrk_contract_address(new_rmrk),
+                Err(ProxyError::TimelockRequired)
+            );
+
+            set_timestamp(10);
+            assert_eq!(contract.propose_mint_price(5), Ok(110));
+            assert!(matches!(
+                last_event(),
+                Event::ChangeProposed(ChangeProposed {
+                    change: ConfigChange::MintPrice(5),
+                    executable_at: 110,
+                })
+            ));
+            assert_eq!(
+                contract.proposals(),
+                vec![Proposal {
+                    change: ConfigChange::MintPrice(5),
+                    executable_at: 110,
+                }]
+            );
+
+            set_timestamp(109);
+            assert_eq!(
+                contract.execute_mint_price(),
+                Err(ProxyError::TimelockNotExpired)
+            );
+            set_timestamp(110);
+            assert!(contract.execute_mint_price().is_ok());
+            assert_eq!(contract.mint_price(), 5);
+            assert!(matches!(
+                last_event(),
+                Event::MintPriceChanged(MintPriceChanged { price: 5 })
+            ));
+            assert_eq!(contract.proposals(), Vec::new());
+            assert_eq!(
+                contract.execute_mint_price(),
+                Err(ProxyError::ProposalNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn propose_replaces_pending_change() {
+            let mut contract = init_contract();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            let first: AccountId = [0x43; 32].into();
+            let second: AccountId = [0x44; 32].into();
+            assert_eq!(contract.propose_catalog_contract_address(first), Ok(100));
+            assert!(contract.propose_rmrk_contract_address(first).is_ok());
+            set_timestamp(50);
+            assert_eq!(contract.propose_catalog_contract_address(second), Ok(150));
+            assert_eq!(contract.proposals().len(), 2);
+
+            set_timestamp(100);
+            assert_eq!(
+                contract.execute_catalog_contract_address(),
+                Err(ProxyError::TimelockNotExpired)
+            );
+            assert!(contract.execute_rmrk_contract_address().is_ok());
+            assert_eq!(contract.rmrk_contract_address(), Some(first));
+            set_timestamp(150);
+            assert!(contract.execute_catalog_contract_address().is_ok());
+            assert_eq!(contract.catalog_contract_address(), Some(second));
+        }
+
+        #[ink::test]
+        fn cancel_proposal_works() {
+            let mut contract = init_contract();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            assert!(contract.propose_mint_price(5).is_ok());
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.cancel_proposal(ConfigKey::MintPrice),
+                Err(missing_role())
+            );
+            assert_eq!(contract.propose_mint_price(6), Err(missing_role()));
+
+            set_sender(default_accounts().alice);
+            assert!(contract.cancel_proposal(ConfigKey::MintPrice).is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::ProposalCancelled(ProposalCancelled {
+                    key: ConfigKey::MintPrice
+                })
+            ));
+            assert_eq!(contract.proposals(), Vec::new());
+            assert_eq!(
+                contract.cancel_proposal(ConfigKey::MintPrice),
+                Err(ProxyError::ProposalNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn timelock_delay_decrease_is_timelocked() {
+            let mut contract = init_contract();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            assert_eq!(
+                contract.set_timelock_delay(0),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(contract.propose_timelock_delay(0), Ok(100));
+            assert_eq!(
+                contract.execute_timelock_delay(),
+                Err(ProxyError::TimelockNotExpired)
+            );
+            set_timestamp(100);
+            assert!(contract.execute_timelock_delay().is_ok());
+            assert_eq!(contract.timelock_delay(), 0);
+            assert!(contract.set_mint_price(5).is_ok());
+        }
+
+        #[ink::test]
+        fn timelock_covers_upgrade_payees_and_pricing() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            assert_eq!(
+                contract.upgrade([0x01; 32]),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(
+                contract.set_payees(vec![(accounts.bob, BASIS_POINTS)]),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(
+                contract.set_pricing_mode(PricingMode::DutchAuction(dutch_auction(false))),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(
+                contract.set_presale_price(Some(5)),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(
+                contract.set_payment_token(Some(accounts.django)),
+                Err(ProxyError::TimelockRequired)
+            );
+
+            assert_eq!(
+                contract.propose_payees(vec![(accounts.bob, 1)]),
+                Err(ProxyError::InvalidPayees)
+            );
+            let mut auction = dutch_auction(false);
+            auction.interval = 0;
+            assert_eq!(
+                contract.propose_pricing_mode(PricingMode::DutchAuction(auction)),
+                Err(ProxyError::InvalidAuction)
+            );
+            assert_eq!(contract.propose_upgrade([0x01; 32]), Ok(100));
+            assert_eq!(
+                contract.propose_payees(vec![(accounts.bob, BASIS_POINTS)]),
+                Ok(100)
+            );
+            assert_eq!(
+                contract.propose_pricing_mode(PricingMode::DutchAuction(dutch_auction(false))),
+                Ok(100)
+            );
+            assert_eq!(contract.propose_presale_price(Some(5)), Ok(100));
+            assert_eq!(
+                contract.propose_payment_token(Some(accounts.django)),
+                Ok(100)
+            );
+            assert_eq!(contract.proposals().len(), 5);
+            assert_eq!(
+                contract.execute_payees(),
+                Err(ProxyError::TimelockNotExpired)
+            );
+
+            set_timestamp(100);
+            assert!(contract.execute_payees().is_ok());
+            assert_eq!(contract.payees(), vec![(accounts.bob, BASIS_POINTS)]);
+            assert!(contract.execute_pricing_mode().is_ok());
+            assert_eq!(
+                contract.pricing_mode(),
+                PricingMode::DutchAuction(dutch_auction(false))
+            );
+            assert_eq!(contract.auction_id(), 1);
+            assert!(contract.execute_presale_price().is_ok());
+            assert_eq!(contract.presale_price(), Some(5));
+            assert!(contract.execute_payment_token().is_ok());
+            assert_eq!(contract.payment_token(), Some(accounts.django));
+            assert_eq!(
+                contract.proposals(),
+                vec![Proposal {
+                    change: ConfigChange::Upgrade([0x01; 32]),
+                    executable_at: 100,
+                }]
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(contract.execute_upgrade(), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn timelock_covers_phase_prices() {
+            let mut contract = init_contract();
+            assert!(contract.add_phase(phase(100, 200)).is_ok());
+            assert!(contract.add_phase(phase(300, 400)).is_ok());
+            assert!(contract.set_timelock_delay(150).is_ok());
+            assert_eq!(
+                contract.set_phase_price(0, 5),
+                Err(ProxyError::TimelockRequired)
+            );
+            let cheaper = SalePhase {
+                price: 5,
+                ..phase(300, 400)
+            };
+            assert_eq!(
+                contract.update_phase(1, cheaper),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(contract.add_phase(phase(400, 500)), Ok(2));
+            assert_eq!(
+                contract.update_phase(2, phase(100, 500)),
+                Err(ProxyError::TimelockRequired)
+            );
+
+            assert_eq!(contract.propose_phase_price(0, 5), Ok(150));
+            assert_eq!(contract.propose_phase_price(1, 6), Ok(150));
+            assert_eq!(contract.propose_phase_price(2, 7), Ok(150));
+            assert_eq!(
+                contract.propose_phase_price(3, 8),
+                Err(ProxyError::PhaseNotFound)
+            );
+            assert!(contract.remove_phase(2).is_ok());
+            assert_eq!(
+                contract.execute_phase_price(2),
+                Err(ProxyError::ProposalNotFound)
+            );
+
+            set_timestamp(150);
+            assert_eq!(
+                contract.execute_phase_price(0),
+                Err(ProxyError::PhaseAlreadyStarted)
+            );
+            assert!(contract.execute_phase_price(1).is_ok());
+            assert_eq!(contract.phases()[1].price, 6);
+
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.propose_phase_price(1, 1), Err(missing_role()));
+            assert_eq!(contract.execute_phase_price(0), Err(missing_role()));
+        }
+
+        #[ink::test]
+        fn timelock_covers_randomness() {
+            let mut contract = init_contract();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            let oracle = RandomnessConfig::Oracle {
+                contract: [0x44; 32].into(),
+                selector: [0x01, 0x02, 0x03, 0x04],
+            };
+            assert_eq!(
+                contract.set_randomness(oracle.clone()),
+                Err(ProxyError::TimelockRequired)
+            );
+            assert_eq!(contract.propose_randomness(oracle.clone()), Ok(100));
+            assert_eq!(
+                contract.execute_randomness(),
+                Err(ProxyError::TimelockNotExpired)
+            );
+
+            set_timestamp(100);
+            set_sender(default_accounts().bob);
+            assert_eq!(contract.execute_randomness(), Err(missing_role()));
+            set_sender(default_accounts().alice);
+            assert!(contract.execute_randomness().is_ok());
+            assert_eq!(contract.randomness(), oracle);
+        }
+
+        #[ink::test]
+        fn failed_execution_keeps_proposal() {
+            let mut contract = init_commit_reveal();
+            assert!(contract.set_timelock_delay(100).is_ok());
+            let token = default_accounts().django;
+            assert!(contract.propose_payment_token(Some(token)).is_ok());
+
+            set_timestamp(100);
+            assert_eq!(
+                contract.execute_payment_token(),
+                Err(ProxyError::NativePaymentRequired)
+            );
+            assert_eq!(contract.payment_token(), None);
+            assert_eq!(contract.proposals().len(), 1);
+        }
+
+        #[ink::test]
+        fn admin_transfer_works() {
+            let mut contract = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.transfer_ownership(accounts.bob),
+                Err(missing_role())
+            );
+
+            set_sender(accounts.alice);
+            assert!(contract.renounce_role(PAUSER, accounts.alice).is_ok());
+            assert!(contract.transfer_ownership(accounts.bob).is_ok());
+            assert_eq!(contract.pending_admin(), Some(accounts.bob));
+            assert!(!contract.has_role(ADMIN, accounts.bob));
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                contract.accept_ownership(),
+                Err(ProxyError::NotPendingAdmin)
+            );
+
+            set_sender(accounts.bob);
+            assert!(contract.accept_ownership().is_ok());
+            assert!(matches!(
+                last_event(),
+                Event::AdminTransferred(AdminTransferred { from, to })
+                    if from == accounts.alice && to == accounts.bob
+            ));
+            for role in [ADMIN, PRICE_MANAGER, CONFIG_MANAGER, RESERVE_MINTER] {
+                assert!(contract.has_role(role, accounts.bob));
+                assert!(!contract.has_role(role, accounts.alice));
+            }
+            assert!(!contract.has_role(PAUSER, accounts.bob));
+            assert_eq!(contract.pending_admin(), None);
+            assert_eq!(
+                contract.accept_ownership(),
+                Err(ProxyError::NotPendingAdmin)
+            );
+        }
+
+        #[ink::test]
+        fn auction_price_decays_to_floor() {
+            let auction = dutch_auction(false);
+            assert_eq!(auction.price_at(0), 1000);
+            assert_eq!(auction.price_at(109), 1000);
+            assert_eq!(auction.price_at(110), 900);
+            assert_eq!(auction.price_at(159), 600);
+            assert_eq!(auction.price_at(160), 400);
+            assert_eq!(auction.price_at(Timestamp::MAX), 400);
+        }
+
+        #[ink::test]
+        fn current_price_follows_auction() {
+            let mut contract = init_contract();
+            assert_eq!(contract.current_price(), Ok(1_000_000_000_000_000_000));
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(false)))
+                .is_ok());
+            assert_eq!(contract.auction_id(), 1);
+            assert_eq!(contract.current_price(), Err(ProxyError::SaleNotActive));
+            set_timestamp(125);
+            assert_eq!(contract.current_price(), Ok(800));
+
+            // Presale keeps its own price.
+            init_presale(&mut contract, default_accounts().alice, 1);
+            assert_eq!(contract.current_price(), Ok(PRESALE_PRICE));
+        }
+
+        #[ink::test]
+        fn set_pricing_mode_validates_auction() {
+            let mut contract = init_contract();
+            let mut auction = dutch_auction(false);
+            auction.interval = 0;
+            assert_eq!(
+                contract.set_pricing_mode(PricingMode::DutchAuction(auction)),
+                Err(ProxyError::InvalidAuction)
+            );
+            let mut auction = dutch_auction(false);
+            auction.floor_price = 1001;
+            assert_eq!(
+                contract.set_pricing_mode(PricingMode::DutchAuction(auction)),
+                Err(ProxyError::InvalidAuction)
+            );
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                contract.set_pricing_mode(PricingMode::Fixed),
+                Err(missing_role())
+            );
         }
 
-        /// Sets a catalog contract address.
-        #[ink(message)]
-        #[modifiers(only_owner)]
-        pub fn set_catalog_contract_address(
-            &mut self,
-            new_contract_address: AccountId,
-        ) -> Result<(), ProxyError> {
-            self.proxy.catalog_contract = Option::Some(new_contract_address);
-            Ok(())
+        #[ink::test]
+        fn mint_fails_if_auction_price_not_covered() {
+            let mut contract = init_contract();
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(false)))
+                .is_ok());
+            set_timestamp(100);
+            set_value(1999);
+            assert_eq!(contract.mint_many(2, None), Err(ProxyError::BadMintValue));
         }
 
-        /// Sets a minting price.
-        #[ink(message)]
-        #[modifiers(only_owner)]
-        pub fn set_mint_price(&mut self, new_mint_price: Balance) -> Result<(), ProxyError> {
-            self.proxy.mint_price = new_mint_price;
-            Ok(())
-        }
+        #[ink::test]
+        fn end_auction_works_without_sales() {
+            let mut contract = init_contract();
+            assert_eq!(contract.end_auction(), Err(ProxyError::AuctionNotRunning));
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(true)))
+                .is_ok());
+            assert_eq!(contract.claim_rebate(1), Err(ProxyError::AuctionNotEnded));
 
-        /// Generates pseudo random number, Used to pick a random asset for a token.
-        fn get_pseudo_random(&mut self, max_value: u8) -> u8 {
-            let seed = self.env().block_timestamp();
-            let mut input: Vec<u8> = Vec::new();
-            input.extend_from_slice(&seed.to_be_bytes());
-            input.extend_from_slice(&self.proxy.salt.to_be_bytes());
-            let mut output = <hash::Keccak256 as hash::HashOutput>::Type::default();
-            ink::env::hash_bytes::<hash::Keccak256>(&input, &mut output);
-            self.proxy.salt += 1;
-            output[0] % (max_value + 1)
+            set_timestamp(130);
+            assert_eq!(contract.end_auction(), Ok(700));
+            assert!(matches!(
+                last_event(),
+                Event::AuctionEnded(AuctionEnded {
+                    auction_id: 1,
+                    clearing_price: 700,
+                })
+            ));
+            assert_eq!(contract.clearing_price(1), Some(700));
+            assert_eq!(contract.end_auction(), Err(ProxyError::AuctionNotRunning));
+            assert_eq!(contract.current_price(), Err(ProxyError::SaleNotActive));
+            assert_eq!(contract.sale_status(), SaleStatus::Ended);
+            assert_eq!(contract.claim_rebate(1), Err(ProxyError::NoRebate));
         }
-    }
-
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use ink::env::test;
 
         #[ink::test]
-        fn constructor_works() {
-            let contract = init_contract();
-            assert_eq!(contract.rmrk_contract_address(), rmrk_address());
-            assert_eq!(contract.catalog_contract_address(), catalog_address());
-            assert_eq!(contract.mint_price(), 1_000_000_000_000_000_000);
+        fn anyone_can_end_auction_at_floor_price() {
+            let mut contract = init_contract();
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(true)))
+                .is_ok());
+            set_sender(default_accounts().bob);
+            set_timestamp(150);
+            assert_eq!(contract.end_auction(), Err(missing_role()));
+            assert_eq!(contract.sale_status(), SaleStatus::Public);
+
+            set_timestamp(160);
+            assert_eq!(contract.end_auction(), Ok(400));
+            assert_eq!(contract.sale_status(), SaleStatus::Ended);
         }
 
         #[ink::test]
-        fn set_rmrk_contract_address_works() {
+        fn anyone_can_end_auction_once_sold_out() {
             let mut contract = init_contract();
-            let new_rmrk: AccountId = [0x43; 32].into();
-            assert!(contract.set_rmrk_contract_address(new_rmrk).is_ok());
-            assert_eq!(contract.rmrk_contract_address(), new_rmrk);
+            assert!(contract.set_max_supply(Some(1)).is_ok());
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(true)))
+                .is_ok());
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            set_timestamp(100);
+            assert_eq!(contract.end_auction(), Err(missing_role()));
+            set_value(1000);
+            assert!(contract.collect_payment(bob, 1, None).is_ok());
+
+            assert_eq!(contract.end_auction(), Ok(1000));
+            assert_eq!(contract.claim_rebate(1), Ok(0));
         }
 
         #[ink::test]
-        fn set_rmrk_contract_address_fails_if_not_owner() {
+        fn claim_rebate_refunds_down_to_clearing_price() {
             let mut contract = init_contract();
-            let new_rmrk: AccountId = [0x43; 32].into();
-            set_sender(default_accounts().bob);
+            let accounts = default_accounts();
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(true)))
+                .is_ok());
+            // Bob buys two tokens at 1000 and overpays, Charlie buys the last one at 800.
+            set_timestamp(100);
+            set_sender(accounts.bob);
+            set_value(2100);
+            let balance_before = balance_of(accounts.bob);
+            assert!(contract.collect_payment(accounts.bob, 2, None).is_ok());
+            assert_eq!(balance_of(accounts.bob), balance_before + 100);
+            set_timestamp(120);
+            set_sender(accounts.charlie);
+            set_value(800);
+            assert!(contract.collect_payment(accounts.charlie, 1, None).is_ok());
+
+            set_sender(accounts.alice);
             assert_eq!(
-                contract.set_rmrk_contract_address(new_rmrk),
-                Err(ProxyError::OwnableError(OwnableError::CallerIsNotOwner))
+                contract.set_pricing_mode(PricingMode::Fixed),
+                Err(ProxyError::AuctionNotEnded)
             );
+
+            assert_eq!(contract.end_auction(), Ok(800));
+            assert_eq!(contract.pending_payment(accounts.alice), 3 * 400);
+            assert!(contract.set_pricing_mode(PricingMode::Fixed).is_ok());
+
+            set_sender(accounts.bob);
+            let balance_before = balance_of(accounts.bob);
+            assert_eq!(contract.claim_rebate(1), Ok(400));
+            assert_eq!(balance_of(accounts.bob), balance_before + 400);
+            assert_eq!(contract.claim_rebate(1), Err(ProxyError::NoRebate));
+            set_sender(accounts.charlie);
+            assert_eq!(contract.claim_rebate(1), Ok(0));
         }
 
         #[ink::test]
-        fn set_catalog_contract_address_works() {
-            let mut contract = init_contract();
-            let new_rmrk: AccountId = [0x43; 32].into();
-            assert!(contract.set_catalog_contract_address(new_rmrk).is_ok());
-            assert_eq!(contract.catalog_contract_address(), new_rmrk);
+        fn commit_mint_fails_during_auction() {
+            let mut contract = init_commit_reveal();
+            assert!(contract
+                .set_pricing_mode(PricingMode::DutchAuction(dutch_auction(false)))
+                .is_ok());
+            let bob = default_accounts().bob;
+            set_timestamp(100);
+            set_sender(bob);
+            set_value(1000);
+            assert_eq!(
+                contract.commit_reservation(commitment_hash(&secret(), &bob), None),
+                Err(ProxyError::FixedPriceRequired)
+            );
         }
 
         #[ink::test]
-        fn set_catalog_contract_address_fails_if_not_owner() {
+        fn quote_price_follows_bonding_curve() {
             let mut contract = init_contract();
-            let new_rmrk: AccountId = [0x43; 32].into();
-            set_sender(default_accounts().bob);
-            assert_eq!(
-                contract.set_catalog_contract_address(new_rmrk),
-                Err(ProxyError::OwnableError(OwnableError::CallerIsNotOwner))
-            );
+            assert_eq!(contract.quote_price(2), Ok(2 * 1_000_000_000_000_000_000));
+            assert!(contract
+                .set_pricing_mode(PricingMode::BondingCurve(linear_curve()))
+                .is_ok());
+            assert_eq!(contract.current_price(), Ok(100));
+            assert_eq!(contract.quote_price(3), Ok(100 + 110 + 120));
+
+            set_value(100 + 110 + 120 + 130);
+            assert!(contract
+                .collect_payment(default_accounts().alice, 4, None)
+                .is_ok());
+            assert_eq!(contract.current_price(), Ok(140));
+            assert_eq!(contract.quote_price(3), Ok(140 + 150 + 150));
+            assert_eq!(contract.quote_price(0), Ok(0));
         }
 
         #[ink::test]
-        fn set_mint_price_works() {
+        fn bonding_curve_ignores_reservations() {
+            let mut contract = init_commit_reveal();
+            let bob = default_accounts().bob;
+            set_sender(bob);
+            set_value(COMMIT_PRICE);
+            assert!(contract
+                .commit_reservation(commitment_hash(&secret(), &bob), None)
+                .is_ok());
+            assert_eq!(contract.total_minted(), 1);
+
+            set_sender(default_accounts().alice);
+            assert!(contract
+                .set_pricing_mode(PricingMode::BondingCurve(linear_curve()))
+                .is_ok());
+            assert_eq!(contract.current_price(), Ok(100));
+        }
+
+        #[ink::test]
+        fn mint_fails_if_curve_price_not_covered() {
             let mut contract = init_contract();
-            assert!(contract.set_mint_price(100).is_ok());
-            assert_eq!(contract.mint_price(), 100);
+            assert!(contract
+                .set_pricing_mode(PricingMode::BondingCurve(linear_curve()))
+                .is_ok());
+            set_value(329);
+            assert_eq!(contract.mint_many(3, None), Err(ProxyError::BadMintValue));
+            assert_eq!(contract.total_minted(), 0);
         }
 
         #[ink::test]
-        fn set_mint_price_fails_if_not_owner() {
+        fn set_pricing_mode_validates_curve() {
             let mut contract = init_contract();
-            set_sender(default_accounts().bob);
+            let mut curve = linear_curve();
+            curve.cap = 99;
             assert_eq!(
-                contract.set_mint_price(100),
-                Err(ProxyError::OwnableError(OwnableError::CallerIsNotOwner))
+                contract.set_pricing_mode(PricingMode::BondingCurve(curve)),
+                Err(ProxyError::InvalidCurve)
             );
+            assert_eq!(contract.pricing_mode(), PricingMode::Fixed);
         }
 
-        #[ink::test]
-        fn mint_fails_if_no_balance() {
+        const PRESALE_PRICE: Balance = 500_000_000_000_000_000;
+        const PHASE_PRICE: Balance = 2_000_000_000_000_000_000;
+
+        fn phase(start: Timestamp, end: Timestamp) -> SalePhase {
+            SalePhase {
+                start,
+                end,
+                price: PHASE_PRICE,
+                max_per_wallet: None,
+                allowlist_root: None,
+            }
+        }
+
+        /// Starts presale with a single leaf allowlist, so the leaf is the root and proof is empty.
+        fn init_presale(contract: &mut RmrkProxy, account: AccountId, quota: u32) {
+            let root = merkle::allowlist_leaf(&account, quota);
+            assert!(contract.set_allowlist_root(Some(root)).is_ok());
+            assert!(contract.set_presale_price(Some(PRESALE_PRICE)).is_ok());
+        }
+
+        /// Returns given seeds in turn.
+        fn dutch_auction(rebate: bool) -> DutchAuction {
+            DutchAuction {
+                start: 100,
+                start_price: 1000,
+                floor_price: 400,
+                decay: 100,
+                interval: 10,
+                rebate,
+            }
+        }
+
+        fn linear_curve() -> BondingCurve {
+            BondingCurve {
+                base_price: 100,
+                curve: Curve::Linear { slope: 10 },
+                cap: 150,
+            }
+        }
+
+        struct MockRandomness {
+            seeds: Vec<[u8; 32]>,
+            subjects: Vec<Vec<u8>>,
+        }
+
+        impl MockRandomness {
+            fn new(seeds: &[[u8; 32]]) -> Self {
+                Self {
+                    seeds: seeds.iter().rev().copied().collect(),
+                    subjects: Vec::new(),
+                }
+            }
+        }
+
+        impl RandomnessSource for MockRandomness {
+            fn random_seed(&mut self, subject: &[u8]) -> Result<[u8; 32], ProxyError> {
+                self.subjects.push(subject.to_vec());
+                Ok(self.seeds.pop().expect("no seeds left"))
+            }
+        }
+
+        /// Builds a seed out of given draws, remaining chunks are filled with `u64::MAX`.
+        fn seed(draws: &[u64]) -> [u8; 32] {
+            let mut seed = [0xff; 32];
+            for (chunk, draw) in seed.chunks_exact_mut(8).zip(draws) {
+                chunk.copy_from_slice(&draw.to_be_bytes());
+            }
+            seed
+        }
+
+        fn missing_role() -> ProxyError {
+            ProxyError::AccessControl(AccessControlError::MissingRole)
+        }
+
+        fn last_event() -> Event {
+            let event = test::recorded_events().last().expect("no events emitted");
+            <Event as scale::Decode>::decode(&mut &event.data[..]).expect("invalid event")
+        }
+
+        fn pseudo_random() -> PseudoRandom {
+            PseudoRandom::new(ink::env::block_timestamp::<ink::env::DefaultEnvironment>())
+        }
+
+        const COMMIT_PRICE: Balance = 100;
+
+        fn commit_reveal_config() -> CommitRevealConfig {
+            CommitRevealConfig {
+                reveal_delay: 2,
+                reveal_window: 5,
+            }
+        }
+
+        fn init_commit_reveal() -> RmrkProxy {
             let mut contract = init_contract();
-            let mint_result = contract.mint();
-            assert_eq!(mint_result, Err(ProxyError::BadMintValue));
+            assert!(contract.set_mint_price(COMMIT_PRICE).is_ok());
+            assert!(contract
+                .set_commit_reveal(Some(commit_reveal_config()))
+                .is_ok());
+            contract
+        }
+
+        fn secret() -> Hash {
+            Hash::from([0x02; 32])
         }
 
         fn init_contract() -> RmrkProxy {
@@ -337,18 +4422,46 @@ mod rmrk_proxy {
         fn set_sender(sender: AccountId) {
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
         }
+
+        fn set_value(value: Balance) {
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(value);
+        }
+
+        fn set_timestamp(timestamp: Timestamp) {
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+        }
+
+        fn advance_blocks(count: BlockNumber) {
+            for _ in 0..count {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+        }
+
+        fn balance_of(account: AccountId) -> Balance {
+            ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account).unwrap()
+        }
     }
 
     #[cfg(all(test, feature = "e2e-tests"))]
     mod e2e_tests {
-        use crate::proxy::rmrk_proxy::RmrkProxyRef;
+        use crate::{
+            proxy::rmrk_proxy::RmrkProxyRef,
+            ProxyError,
+        };
         use catalog_example::catalog_example::CatalogContractRef;
         use ink::primitives::AccountId;
         use ink_e2e::build_message;
-        use openbrush::contracts::psp34::{
-            psp34_external::PSP34,
-            Id,
+        use openbrush::contracts::{
+            psp22::{
+                psp22_external::PSP22,
+                PSP22Error,
+            },
+            psp34::{
+                psp34_external::PSP34,
+                Id,
+            },
         };
+        use psp22_example::psp22_example::Psp22ContractRef;
         use rmrk::{
             storage::catalog_external::Catalog,
             traits::multiasset_external::MultiAsset,
@@ -408,7 +4521,7 @@ mod rmrk_proxy {
                 1_000_000_000_000_000_000,
                 String::from("ipfs://collection").into(),
                 AccountId::try_from(alice.account_id().as_ref()).unwrap(),
-                1,
+                100,
             );
             let rmrk_address = client
                 .instantiate("rmrk_equippable_lazy", &alice, rmrk_constructor, 0, None)
@@ -454,11 +4567,13 @@ mod rmrk_proxy {
 
             // Mint token.
             let mint_message =
-                build_message::<RmrkProxyRef>(proxy_address.clone()).call(|proxy| proxy.mint());
-            client
+                build_message::<RmrkProxyRef>(proxy_address.clone()).call(|proxy| proxy.mint(None));
+            let mint_result = client
                 .call(&alice, mint_message, 1_000_000_000_000_000_000, None)
                 .await
                 .expect("Mint failed");
+            assert!(mint_result.contains_event("Contracts", "ContractEmitted"));
+            assert_eq!(mint_result.return_value(), Ok(Id::U64(1)));
 
             // Check if token was minted
             let read_total_supply_message =
@@ -497,6 +4612,257 @@ mod rmrk_proxy {
 
             Ok(())
         }
+
+        #[ink_e2e::test]
+        async fn mint_with_payment_token_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let alice = ink_e2e::alice();
+            let alice_account_id = AccountId::try_from(alice.account_id().as_ref()).unwrap();
+
+            // *************** Create catalog contract and add parts ***************
+            let catalog_constructor = CatalogContractRef::new(String::from("ipfs://").into());
+            let catalog_contract_address = client
+                .instantiate("catalog_example", &alice, catalog_constructor, 0, None)
+                .await
+                .expect("Catalog contract instantiation failed")
+                .account_id;
+
+            let parts = vec![Part {
+                part_type: PartType::Fixed,
+                z: 0,
+                equippable: vec![],
+                part_uri: String::from("ipfs://").into(),
+                is_equippable_by_all: false,
+            }];
+            let add_part_message =
+                build_message::<CatalogContractRef>(catalog_contract_address.clone())
+                    .call(|catalog| catalog.add_part_list(vec![0], parts.clone()));
+            client
+                .call(&alice, add_part_message, 0, None)
+                .await
+                .expect("Add part failed");
+
+            // *************** Create free RMRK contract and add asset entry ***************
+            // Mints paid in a PSP22 token leave no native value to pay a lazy mint price with.
+            let rmrk_constructor = RmrkRef::new(
+                String::from("Test").into(),
+                String::from("TST").into(),
+                String::from("ipfs://base").into(),
+                None,
+                0,
+                String::from("ipfs://collection").into(),
+                alice_account_id,
+                100,
+            );
+            let rmrk_address = client
+                .instantiate("rmrk_equippable_lazy", &alice, rmrk_constructor, 0, None)
+                .await
+                .expect("RMRK contract instantiation failed")
+                .account_id;
+
+            let add_asset_entry_message =
+                build_message::<RmrkRef>(rmrk_address.clone()).call(|rmrk| {
+                    rmrk.add_asset_entry(
+                        Some(catalog_contract_address.clone()),
+                        1,
+                        1,
+                        String::from("ipfs://parturi").into(),
+                        vec![0],
+                    )
+                });
+            client
+                .call(&alice, add_asset_entry_message, 0, None)
+                .await
+                .expect("Add asset entry failed");
+
+            // *************** Create PSP22 token and RMRK proxy contract ***************
+            let token_constructor = Psp22ContractRef::new(1_000);
+            let token_address = client
+                .instantiate("psp22_example", &alice, token_constructor, 0, None)
+                .await
+                .expect("PSP22 contract instantiation failed")
+                .account_id;
+
+            let proxy_constructor = RmrkProxyRef::new(rmrk_address, catalog_contract_address, 100);
+            let proxy_address = client
+                .instantiate("rmrk_proxy", &alice, proxy_constructor, 0, None)
+                .await
+                .expect("Proxy contract instantiation failed")
+                .account_id;
+
+            let set_payment_token_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.set_payment_token(Some(token_address)));
+            client
+                .call(&alice, set_payment_token_message, 0, None)
+                .await
+                .expect("Set payment token failed");
+
+            // Minting fails until the proxy may take the price out of the caller's tokens.
+            let mint_message =
+                build_message::<RmrkProxyRef>(proxy_address.clone()).call(|proxy| proxy.mint(None));
+            let mint_result = client
+                .call_dry_run(&alice, &mint_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(
+                mint_result,
+                Err(ProxyError::TokenTransferFailed(
+                    PSP22Error::InsufficientAllowance
+                ))
+            );
+
+            let approve_message = build_message::<Psp22ContractRef>(token_address.clone())
+                .call(|token| token.approve(proxy_address, 100));
+            client
+                .call(&alice, approve_message, 0, None)
+                .await
+                .expect("Approve failed");
+
+            // Mint token.
+            let mint_message =
+                build_message::<RmrkProxyRef>(proxy_address.clone()).call(|proxy| proxy.mint(None));
+            let mint_result = client
+                .call(&alice, mint_message, 0, None)
+                .await
+                .expect("Mint failed");
+            assert_eq!(mint_result.return_value(), Ok(Id::U64(1)));
+
+            let read_owner_of_message = build_message::<RmrkRef>(rmrk_address.clone())
+                .call(|rmrk| rmrk.owner_of(Id::U64(1)));
+            let read_owner_of_result = client
+                .call_dry_run(&alice, &read_owner_of_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(read_owner_of_result, Some(alice_account_id));
+
+            // Check the price moved to the proxy and is pending for the payee.
+            let read_balance_message = build_message::<Psp22ContractRef>(token_address.clone())
+                .call(|token| token.balance_of(proxy_address));
+            let read_balance_result = client
+                .call_dry_run(&alice, &read_balance_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(read_balance_result, 100);
+
+            let read_pending_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.pending_token_payment(token_address, alice_account_id));
+            let read_pending_result = client
+                .call_dry_run(&alice, &read_pending_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(read_pending_result, 100);
+
+            // Withdraw proceeds in the payment token.
+            let withdraw_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.withdraw_token(token_address, alice_account_id));
+            let withdraw_result = client
+                .call(&alice, withdraw_message, 0, None)
+                .await
+                .expect("Withdraw failed");
+            assert_eq!(withdraw_result.return_value(), Ok(100));
+
+            let read_balance_message = build_message::<Psp22ContractRef>(token_address.clone())
+                .call(|token| token.balance_of(alice_account_id));
+            let read_balance_result = client
+                .call_dry_run(&alice, &read_balance_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(read_balance_result, 1_000);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn upgrade_keeps_state(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let alice = ink_e2e::alice();
+            let alice_account_id = AccountId::try_from(alice.account_id().as_ref()).unwrap();
+
+            let proxy_constructor = RmrkProxyRef::new(alice_account_id, alice_account_id, 1);
+            let proxy_address = client
+                .instantiate("rmrk_proxy", &alice, proxy_constructor, 0, None)
+                .await
+                .expect("Proxy contract instantiation failed")
+                .account_id;
+
+            let set_price_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.set_mint_price(5));
+            client
+                .call(&alice, set_price_message, 0, None)
+                .await
+                .expect("Set mint price failed");
+
+            // Upgrade to the same code, storage must stay untouched.
+            let code_hash_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.code_hash());
+            let code_hash: [u8; 32] = client
+                .call_dry_run(&alice, &code_hash_message, 0, None)
+                .await
+                .return_value()
+                .unwrap()
+                .as_ref()
+                .try_into()
+                .unwrap();
+            let upgrade_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.upgrade(code_hash));
+            let upgrade_result = client
+                .call(&alice, upgrade_message, 0, None)
+                .await
+                .expect("Upgrade failed");
+            assert_eq!(upgrade_result.return_value(), Ok(()));
+
+            let mint_price_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.mint_price());
+            let mint_price = client
+                .call_dry_run(&alice, &mint_price_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(mint_price, 5);
+
+            let migrate_message =
+                build_message::<RmrkProxyRef>(proxy_address.clone()).call(|proxy| proxy.migrate());
+            let migrate_result = client
+                .call_dry_run(&alice, &migrate_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(migrate_result, Err(ProxyError::NothingToMigrate));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn mint_fails_if_total_assets_query_fails(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            let alice = ink_e2e::alice();
+
+            let catalog_constructor = CatalogContractRef::new(String::from("ipfs://").into());
+            let catalog_contract_address = client
+                .instantiate("catalog_example", &alice, catalog_constructor, 0, None)
+                .await
+                .expect("Catalog contract instantiation failed")
+                .account_id;
+
+            // Catalog contract doesn't implement MultiAsset, so querying total assets fails.
+            let proxy_constructor = RmrkProxyRef::new(
+                catalog_contract_address.clone(),
+                catalog_contract_address,
+                1_000_000_000_000_000_000,
+            );
+            let proxy_address = client
+                .instantiate("rmrk_proxy", &alice, proxy_constructor, 0, None)
+                .await
+                .expect("Proxy contract instantiation failed")
+                .account_id;
+
+            let mint_message =
+                build_message::<RmrkProxyRef>(proxy_address.clone()).call(|proxy| proxy.mint(None));
+            let mint_result = client
+                .call_dry_run(&alice, &mint_message, 1_000_000_000_000_000_000, None)
+                .await
+                .return_value();
+            assert_eq!(mint_result, Err(ProxyError::TotalAssetsQueryFailed));
+
+            Ok(())
+        }
     }
 }
 
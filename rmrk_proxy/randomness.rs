@@ -0,0 +1,144 @@
+//! Sources of randomness used to pick an asset for a minted token.
+//!
+//! A source turns a `subject` into a 32 byte seed. The proxy passes a fresh subject on every
+//! call, so a source can be asked for several seeds within a single block.
+
+use crate::{
+    client,
+    ProxyError,
+};
+use ink::{
+    env::hash::{
+        HashOutput,
+        Keccak256,
+    },
+    prelude::vec::Vec,
+    primitives::Hash,
+};
+use openbrush::traits::{
+    AccountId,
+    Timestamp,
+};
+
+pub trait RandomnessSource {
+    /// Returns a random seed for `subject`.
+    fn random_seed(&mut self, subject: &[u8]) -> Result<[u8; 32], ProxyError>;
+}
+
+/// Hashes block timestamp with the subject. Cheap, but predictable by block producers
+/// and by anyone who can simulate the call.
+pub struct PseudoRandom {
+    timestamp: Timestamp,
+}
+
+impl PseudoRandom {
+    pub fn new(timestamp: Timestamp) -> Self {
+        Self { timestamp }
+    }
+}
+
+impl RandomnessSource for PseudoRandom {
+    fn random_seed(&mut self, subject: &[u8]) -> Result<[u8; 32], ProxyError> {
+        let mut input: Vec<u8> = Vec::new();
+        input.extend_from_slice(&self.timestamp.to_be_bytes());
+        input.extend_from_slice(subject);
+        Ok(keccak256(&input))
+    }
+}
+
+/// Hashes a secret revealed by the minter with entropy fixed only after the secret was committed
+/// to. Seeds don't depend on the reveal block or on the subject, which changes with every other
+/// mint, so a reservation has a single outcome however its reveal is timed.
+pub struct CommitRevealRandom {
+    reservation_id: u64,
+    secret: Hash,
+    entropy: Hash,
+    draws: u32,
+}
+
+impl CommitRevealRandom {
+    pub fn new(reservation_id: u64, secret: Hash, entropy: Hash) -> Self {
+        Self {
+            reservation_id,
+            secret,
+            entropy,
+            draws: 0,
+        }
+    }
+}
+
+impl RandomnessSource for CommitRevealRandom {
+    fn random_seed(&mut self, _subject: &[u8]) -> Result<[u8; 32], ProxyError> {
+        let mut input: Vec<u8> = Vec::new();
+        input.extend_from_slice(&self.reservation_id.to_be_bytes());
+        input.extend_from_slice(self.secret.as_ref());
+        input.extend_from_slice(self.entropy.as_ref());
+        input.extend_from_slice(&self.draws.to_be_bytes());
+        self.draws += 1;
+        Ok(keccak256(&input))
+    }
+}
+
+/// Asks an external oracle contract for the seed. The oracle message is called with the subject
+/// as its only argument, `Vec<u8>`, and has to return `[u8; 32]`.
+pub struct OracleRandom {
+    contract: AccountId,
+    selector: [u8; 4],
+}
+
+impl OracleRandom {
+    pub fn new(contract: AccountId, selector: [u8; 4]) -> Self {
+        Self { contract, selector }
+    }
+}
+
+impl RandomnessSource for OracleRandom {
+    fn random_seed(&mut self, subject: &[u8]) -> Result<[u8; 32], ProxyError> {
+        client::random_seed(self.contract, self.selector, subject)
+    }
+}
+
+fn keccak256(input: &[u8]) -> [u8; 32] {
+    let mut output = <Keccak256 as HashOutput>::Type::default();
+    ink::env::hash_bytes::<Keccak256>(input, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ink::test]
+    fn pseudo_random_depends_on_subject_and_timestamp() {
+        let mut source = PseudoRandom::new(1);
+        let seed = source.random_seed(&[0]).unwrap();
+        assert_eq!(source.random_seed(&[0]), Ok(seed));
+        assert_ne!(source.random_seed(&[1]), Ok(seed));
+        assert_ne!(PseudoRandom::new(2).random_seed(&[0]), Ok(seed));
+    }
+
+    #[ink::test]
+    fn commit_reveal_random_depends_on_secret_and_entropy() {
+        let secret = Hash::from([0x01; 32]);
+        let entropy = Hash::from([0x05; 32]);
+        let mut source = CommitRevealRandom::new(1, secret, entropy);
+        let seed = source.random_seed(&[0]).unwrap();
+        assert_ne!(source.random_seed(&[0]), Ok(seed));
+        assert_eq!(
+            CommitRevealRandom::new(1, secret, entropy).random_seed(&[7]),
+            Ok(seed)
+        );
+        assert_ne!(
+            CommitRevealRandom::new(2, secret, entropy).random_seed(&[0]),
+            Ok(seed)
+        );
+        assert_ne!(
+            CommitRevealRandom::new(1, Hash::from([0x02; 32]), entropy).random_seed(&[0]),
+            Ok(seed)
+        );
+        assert_ne!(
+            CommitRevealRandom::new(1, secret, Hash::from([0x06; 32])).random_seed(&[0]),
+            Ok(seed)
+        );
+    }
+}
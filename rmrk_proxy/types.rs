@@ -1,20 +1,70 @@
 use openbrush::{
     contracts::{
-        access_control::AccessControlError,
-        ownable::OwnableError,
+        access_control::{
+            AccessControlError,
+            RoleType,
+            DEFAULT_ADMIN_ROLE,
+        },
+        pausable::PausableError,
+        psp22::PSP22Error,
         psp34::PSP34Error,
         reentrancy_guard::ReentrancyGuardError,
     },
+    storage::Mapping,
     traits::{
         AccountId,
         Balance,
+        BlockNumber,
+        Timestamp,
     },
 };
 
-use rmrk::errors::Error as RmrkError;
+use ink::{
+    prelude::vec::Vec,
+    primitives::Hash,
+    storage::Lazy,
+};
+
+use rmrk::{
+    errors::Error as RmrkError,
+    types::AssetId,
+};
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
 
+/// Version of the storage layout this code works with. Layout 1 is the first one upgrades can
+/// keep. Bump it together with a new step in `migrate` whenever stored data has to be moved
+/// or converted.
+pub const STORAGE_VERSION: u32 = 1;
+
+/// Grants and revokes roles, sets payees and the timelock delay, unpauses minting and upgrades
+/// the contract.
+pub const ADMIN: RoleType = DEFAULT_ADMIN_ROLE;
+/// Sets mint prices.
+pub const PRICE_MANAGER: RoleType = ink::selector_id!("PRICE_MANAGER");
+/// Sets contract addresses, sale schedule, limits and asset distribution.
+pub const CONFIG_MANAGER: RoleType = ink::selector_id!("CONFIG_MANAGER");
+/// Pauses minting in an emergency.
+pub const PAUSER: RoleType = ink::selector_id!("PAUSER");
+/// Mints tokens from the reserve allocation.
+pub const RESERVE_MINTER: RoleType = ink::selector_id!("RESERVE_MINTER");
+
+/// Default maximum number of tokens which can be minted in a single call.
+pub const DEFAULT_MAX_PER_TX: u32 = 10;
+
+/// Phase index which presale without phases is counted under, so that allowlist quotas
+/// and public sale limits don't share mints. Scheduled phases never reach it.
+pub const PRESALE_PHASE: u32 = u32::MAX;
+
+/// Asset probabilities are expressed in parts per million.
+pub const PROBABILITY_PRECISION: u64 = 1_000_000;
+
+/// Share of the payment, in basis points, kept when an expired reservation is refunded.
+pub const DEFAULT_EXPIRY_FEE: u16 = 5_000;
+
+/// Payee shares are expressed in basis points and have to add up to this value.
+pub const BASIS_POINTS: u16 = 10_000;
+
 #[derive(Default, Debug)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
 pub struct Data {
@@ -22,6 +72,303 @@ pub struct Data {
     pub catalog_contract: Option<AccountId>, // Catalog contract address
     pub mint_price: Balance,                 // A token minting price
     pub salt: u64,                           // used for pseudo random number generation
+    pub max_per_tx: u32,                     // Max number of tokens minted in a single call
+    pub allowlist_root: Option<Hash>,        // Presale allowlist Merkle root
+    pub presale_price: Option<Balance>,      // A token minting price for allowlisted accounts
+    pub phases: Vec<SalePhase>,              // Scheduled sale phases ordered by time
+    pub phase_minted: Mapping<(Option<u32>, AccountId), u32>, // Tokens minted per phase and account
+    pub max_per_wallet: Option<u32>,         // Max tokens minted per account in a sale phase
+    pub minted: Mapping<AccountId, u32>,     // Total tokens minted per account
+    pub asset_weights: Vec<(AssetId, u32)>,  // Rarity weight per asset, empty means uniform
+    pub asset_supply: Mapping<AssetId, u32>, // Remaining supply per asset, unlimited if not set
+    pub exhausted_assets: Vec<AssetId>,      // Sorted ids of assets with no remaining supply
+    pub commit_reveal: Option<CommitRevealConfig>, // Commit-reveal settings, off if not set
+    pub expiry_fee: Lazy<u16>,               // Basis points kept from expired reservations
+    pub reservations: Mapping<u64, Reservation>, // Tokens paid for but not revealed yet
+    pub reservation_rmrk_prices: Mapping<u64, Balance>, // RMRK price locked per reservation
+    pub next_reservation_id: u64,            // Id of the next reservation
+    pub entropy: Lazy<(BlockNumber, Hash)>,  // Block of the latest mix and the entropy pool
+    pub entropy_records: Mapping<BlockNumber, (Hash, Option<BlockNumber>)>, // Pool at block start
+    pub randomness: RandomnessConfig,        // Source of randomness for direct mints
+    pub payees: Vec<(AccountId, u16)>,       // Shares of mint proceeds in basis points
+    pub pending_payments: Mapping<AccountId, Balance>, // Proceeds not withdrawn yet per payee
+    pub max_supply: Option<u32>,             // Proxy mint cap, unlimited if not set
+    pub total_minted: u32,                   // Tokens minted or reserved through the proxy
+    pub reserve: u32,                        // Tokens left for reserve mints only
+    pub storage_version: Lazy<u32>,          // Storage layout version, not set before versioning
+    pub timelock_delay: Lazy<Timestamp>,     // Delay of timelocked config changes, zero if not set
+    pub proposals: Lazy<Vec<Proposal>>,      // Pending timelocked config changes, one per setting
+    pub admin_transfer: Lazy<Option<(AccountId, AccountId)>>, // Current and new admin of a transfer
+    pub pricing: Lazy<PricingMode>,          // Public sale pricing, fixed `mint_price` if not set
+    pub auction_id: Lazy<u32>,               // Id of the latest Dutch auction
+    pub auction: Lazy<AuctionState>,         // Sales of the latest Dutch auction
+    pub auction_purchases: Mapping<(u32, AccountId), (u32, Balance)>, // Tokens and paid per buyer
+    pub clearing_prices: Mapping<u32, Balance>, // Final price per ended auction
+    pub payment_token: Lazy<Option<AccountId>>, // PSP22 token prices are in, native if not set
+    pub pending_token_payments: Mapping<(AccountId, AccountId), Balance>, // Per token and payee
+    pub sold: Lazy<u32>,                     // Tokens sold by direct mints, prices bonding curve
+}
+
+/// Proxy storage written by code which predates storage versioning. The current code can't
+/// decode it, so the `rmrk_proxy_migration` contract reads it and writes layout 1 instead.
+#[derive(Default, Debug)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct DataV0 {
+    pub rmrk_contract: Option<AccountId>,    // RMRK contract address
+    pub catalog_contract: Option<AccountId>, // Catalog contract address
+    pub mint_price: Balance,                 // A token minting price
+    pub salt: u64,                           // used for pseudo random number generation
+}
+
+/// Source of randomness used to pick an asset for tokens minted directly with `mint`.
+/// Tokens minted with commit-reveal always use the revealed secret.
+#[derive(Default, Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RandomnessConfig {
+    /// Hash of block timestamp and a salt.
+    #[default]
+    PseudoRandom,
+    /// An oracle contract message called with `Vec<u8>` subject which returns `[u8; 32]` seed.
+    Oracle {
+        contract: AccountId,
+        selector: [u8; 4],
+    },
+}
+
+/// Commit-reveal minting settings, in blocks.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct CommitRevealConfig {
+    /// Number of blocks after commit before the secret can be revealed.
+    pub reveal_delay: BlockNumber,
+    /// Number of blocks the secret can be revealed in. After that the reservation expires.
+    pub reveal_window: BlockNumber,
+}
+
+/// A token paid for with `commit_mint` which is minted once the secret is revealed.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Reservation {
+    pub owner: AccountId,
+    /// `keccak256(SCALE((secret, owner)))`.
+    pub commitment: Hash,
+    /// Block number of the commit.
+    pub committed_at: BlockNumber,
+    /// Value paid for the token.
+    pub paid: Balance,
+    /// Sale phase the token was paid in.
+    pub phase: Option<u32>,
+}
+
+/// A config change which takes effect only after the timelock delay.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ConfigChange {
+    RmrkContract(AccountId),
+    CatalogContract(AccountId),
+    MintPrice(Balance),
+    TimelockDelay(Timestamp),
+    Upgrade([u8; 32]),
+    Payees(Vec<(AccountId, u16)>),
+    PresalePrice(Option<Balance>),
+    PricingMode(PricingMode),
+    PaymentToken(Option<AccountId>),
+    PhasePrice(u32, Balance),
+    Randomness(RandomnessConfig),
+}
+
+impl ConfigChange {
+    pub fn key(&self) -> ConfigKey {
+        match self {
+            Self::RmrkContract(_) => ConfigKey::RmrkContract,
+            Self::CatalogContract(_) => ConfigKey::CatalogContract,
+            Self::MintPrice(_) => ConfigKey::MintPrice,
+            Self::TimelockDelay(_) => ConfigKey::TimelockDelay,
+            Self::Upgrade(_) => ConfigKey::Upgrade,
+            Self::Payees(_) => ConfigKey::Payees,
+            Self::PresalePrice(_) => ConfigKey::PresalePrice,
+            Self::PricingMode(_) => ConfigKey::PricingMode,
+            Self::PaymentToken(_) => ConfigKey::PaymentToken,
+            Self::PhasePrice(index, _) => ConfigKey::PhasePrice(*index),
+            Self::Randomness(_) => ConfigKey::Randomness,
+        }
+    }
+}
+
+/// A setting changed by a `ConfigChange`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ConfigKey {
+    RmrkContract,
+    CatalogContract,
+    MintPrice,
+    TimelockDelay,
+    Upgrade,
+    Payees,
+    PresalePrice,
+    PricingMode,
+    PaymentToken,
+    PhasePrice(u32),
+    Randomness,
+}
+
+/// A pending config change.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Proposal {
+    pub change: ConfigChange,
+    /// Block timestamp from which the change can be executed.
+    pub executable_at: Timestamp,
+}
+
+/// A scheduled sale phase. Phase is active from `start` (inclusive) until `end` (exclusive).
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SalePhase {
+    pub start: Timestamp,
+    pub end: Timestamp,
+    /// A token minting price during the phase.
+    pub price: Balance,
+    /// Max number of tokens a single account can mint during the phase. Falls back to
+    /// the proxy wide `max_per_wallet` if not set.
+    pub max_per_wallet: Option<u32>,
+    /// Merkle root of accounts allowed to mint during the phase. Phase is public if not set.
+    pub allowlist_root: Option<Hash>,
+}
+
+/// A sale status derived from scheduled phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum SaleStatus {
+    /// No phase is active but there are phases scheduled later.
+    Closed,
+    /// Active phase is restricted to allowlisted accounts.
+    Presale,
+    /// Active phase is open to everyone.
+    Public,
+    /// All scheduled phases are over, or the Dutch auction of the sale without phases has ended.
+    Ended,
+}
+
+/// How the price of the public sale without phases is computed.
+#[derive(Default, Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum PricingMode {
+    /// Every token costs `mint_price`.
+    #[default]
+    Fixed,
+    DutchAuction(DutchAuction),
+    BondingCurve(BondingCurve),
+}
+
+/// A price rising with the number of tokens minted through the proxy, up to `cap`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct BondingCurve {
+    /// A price of the first token.
+    pub base_price: Balance,
+    pub curve: Curve,
+    /// Max price of a token.
+    pub cap: Balance,
+}
+
+/// Shape of a bonding curve.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Curve {
+    /// The price grows by `slope` with every minted token.
+    Linear { slope: Balance },
+    /// The price grows by `growth` basis points with every minted token.
+    Exponential { growth: u16 },
+}
+
+/// A descending price auction. The price starts at `start_price` and drops by `decay` every
+/// `interval` milliseconds until it reaches `floor_price`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DutchAuction {
+    /// Block timestamp the auction starts at.
+    pub start: Timestamp,
+    pub start_price: Balance,
+    pub floor_price: Balance,
+    pub decay: Balance,
+    pub interval: Timestamp,
+    /// Refunds buyers down to the clearing price once the auction ends.
+    pub rebate: bool,
+}
+
+impl DutchAuction {
+    /// Gets the auction price at block timestamp `now`.
+    pub fn price_at(&self, now: Timestamp) -> Balance {
+        let steps = now.saturating_sub(self.start) / self.interval;
+        self.start_price
+            .saturating_sub(self.decay.saturating_mul(steps as Balance))
+            .max(self.floor_price)
+    }
+}
+
+/// Sales of a Dutch auction.
+#[derive(Default, Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AuctionState {
+    /// Number of tokens sold.
+    pub sold: u32,
+    /// Price of the latest sale, which becomes the clearing price.
+    pub last_price: Balance,
+}
+
+/// Terms of the currently active sale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaleTerms {
+    /// Index of the active phase. Without phases, `PRESALE_PHASE` during presale
+    /// and `None` otherwise.
+    pub phase: Option<u32>,
+    pub price: Balance,
+    pub max_per_wallet: Option<u32>,
+    pub allowlist_root: Option<Hash>,
+}
+
+/// Proof that an account is allowlisted to mint up to `quota` tokens.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct AllowlistProof {
+    /// Max number of tokens the account can mint during presale.
+    pub quota: u32,
+    /// Sibling hashes on the path from the account leaf to the root.
+    pub proof: Vec<Hash>,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -29,35 +376,125 @@ pub struct Data {
 pub enum ProxyError {
     AccessControl(AccessControlError),
     /// Error happened while trying to add asset to minted token.
-    AddTokenAssetError,
+    AddTokenAssetError(RmrkError),
+    /// A caller would mint more tokens than its allowlist quota.
+    AllowlistQuotaExceeded,
+    /// An asset with given id is not defined on the RMRK contract.
+    AssetNotFound,
+    /// A Dutch auction has to end before its rebates can be claimed or pricing can change.
+    AuctionNotEnded,
+    /// There is no running Dutch auction.
+    AuctionNotRunning,
+    /// A number of tokens to mint is zero.
+    BadMintCount,
     // A value passed to mint method doesn't match mint_price.
     BadMintValue,
-    // An environment error happened while trying to invoke mint method on the RMRK contract.
+    /// Commit-reveal minting is not enabled.
+    CommitRevealDisabled,
+    /// Commit-reveal minting is enabled, tokens have to be minted with `commit_mint`.
+    CommitRevealRequired,
+    // An environment error happened while trying to invoke a method on the RMRK contract.
     EnvironmentError,
-    /// A language error happened while trying to invoke mint method on the RMRK contract.
+    /// Commit-reveal reservations can't be paid at a Dutch auction or bonding curve price.
+    FixedPriceRequired,
+    /// A Dutch auction has zero interval or its floor price is above the start price.
+    InvalidAuction,
+    /// Commit-reveal delay and window have to be greater than zero and the expiry fee can't
+    /// exceed `BASIS_POINTS`.
+    InvalidCommitRevealConfig,
+    /// A bonding curve cap is below its base price.
+    InvalidCurve,
+    /// Payee shares are empty, zero or don't add up to `BASIS_POINTS`.
+    InvalidPayees,
+    /// A sale phase ends before it starts or overlaps with another phase.
+    InvalidPhase,
+    /// A revealed secret doesn't match the commitment.
+    InvalidSecret,
+    /// A max supply is lower than the minted tokens plus the reserve.
+    InvalidSupply,
+    /// A language error happened while trying to invoke a method on the RMRK contract.
     LanguageError,
     /// Something went wrong while invoking mint method on the RMRK contract.
     MintingError,
+    /// Commit-reveal and auction rebates refund in the native token, so they can't be combined
+    /// with a PSP22 payment token.
+    NativePaymentRequired,
     /// No assets defined on RMRK contract.
     NoAssetsDefined,
-    /// A caller is not a marketplace owner.
-    OwnableError(OwnableError),
+    /// A caller has no purchases to rebate in the auction.
+    NoRebate,
+    /// A caller is not in the presale allowlist or the provided proof is invalid.
+    NotAllowlisted,
+    /// A RMRK contract address is not set.
+    NotConfigured,
+    /// Minting is not paused.
+    NotPaused,
+    /// A caller is not the account the admin role is being transferred to.
+    NotPendingAdmin,
+    /// A caller is not the owner of the reservation.
+    NotReservationOwner,
+    /// Storage layout is already up to date.
+    NothingToMigrate,
+    /// A payee has no pending payment.
+    NothingToWithdraw,
     /// Error happened while trying to transfer minted token ownership to a caller.
-    OwnershipTransferError,
+    OwnershipTransferError(PSP34Error),
     /// PSP34 contract error.
     PSP34(PSP34Error),
+    /// Minting is paused.
+    Paused,
+    /// A number of tokens to mint exceeds the per call limit.
+    PerTxLimitExceeded,
+    /// A caller would mint more tokens than allowed for a single account.
+    PerWalletLimitExceeded,
+    /// A sale phase can't be modified after it has started.
+    PhaseAlreadyStarted,
+    /// A sale phase with given index doesn't exist.
+    PhaseNotFound,
+    /// There is no pending proposal for the setting.
+    ProposalNotFound,
+    /// A randomness oracle call failed.
+    RandomnessUnavailable,
     /// A caller is trying to make second call while 1st one is still executing.
     ReentrancyError(ReentrancyGuardError),
+    /// A reservation can't be revealed anymore.
+    ReservationExpired,
+    /// A reservation can't be refunded before it expires.
+    ReservationNotExpired,
+    /// A reservation with given id doesn't exist.
+    ReservationNotFound,
+    /// A reserve mint exceeds the remaining reserve.
+    ReserveExceeded,
+    /// A reservation can't be revealed before the reveal delay passes.
+    RevealTooEarly,
     /// A RMRK contract error.
     RmrkError(rmrk::errors::Error),
-    /// Too many assets defined on RMRK contract. This is a limitation of the current proxy implementation
-    /// where get_pseudo_random function returns u8.
-    TooManyAssetsDefined,
+    /// A sale price is lower than the lazy mint price of the RMRK contract.
+    RmrkPriceNotCovered,
+    /// There is no active sale phase at the current block timestamp.
+    SaleNotActive,
+    /// All assets or the tokens available to public minting have run out.
+    SoldOut,
+    /// A timelocked change can't be executed before its delay passes.
+    TimelockNotExpired,
+    /// A change has to be proposed and executed after the timelock delay.
+    TimelockRequired,
+    /// Transferring PSP22 payment tokens failed, e.g. because of an insufficient allowance.
+    TokenTransferFailed(PSP22Error),
+    /// Querying total assets of the RMRK contract failed.
+    TotalAssetsQueryFailed,
+    /// Transferring native tokens out of the proxy failed.
+    TransferFailed,
+    /// Replacing the contract code failed, e.g. because no code with given hash is uploaded.
+    UpgradeFailed,
 }
 
-impl From<OwnableError> for ProxyError {
-    fn from(error: OwnableError) -> Self {
-        Self::OwnableError(error)
+impl From<PausableError> for ProxyError {
+    fn from(error: PausableError) -> Self {
+        match error {
+            PausableError::Paused => Self::Paused,
+            PausableError::NotPaused => Self::NotPaused,
+        }
     }
 }
 
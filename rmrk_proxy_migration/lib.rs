@@ -0,0 +1,247 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+/// Converts storage of a proxy deployed before storage versioning into the current layout.
+///
+/// Proxy code with versioned storage can't decode the old layout, so the old proxy is first
+/// switched to this code with the `Contracts::set_code` extrinsic. `migrate` then reads the old
+/// layout, writes layout 1 of `RmrkProxy` and switches the contract to the proxy code.
+#[openbrush::contract]
+pub mod rmrk_proxy_migration {
+    use ink::storage::traits::StorageKey;
+    use openbrush::{
+        contracts::{
+            access_control::AccessControlError,
+            ownable::*,
+            reentrancy_guard::*,
+        },
+        traits::Storage,
+    };
+    use rmrk_proxy::{
+        ensure,
+        proxy::rmrk_proxy::RmrkProxy,
+        DataV0,
+        ProxyError,
+        STORAGE_VERSION,
+    };
+
+    // Proxy storage as laid out by code which predates storage versioning
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct LegacyProxy {
+        #[storage_field]
+        ownable: ownable::Data,
+        #[storage_field]
+        guard: reentrancy_guard::Data,
+        #[storage_field]
+        proxy: DataV0,
+    }
+
+    /// Event emitted when storage is migrated to a newer layout.
+    #[ink(event)]
+    pub struct Migrated {
+        from: u32,
+        to: u32,
+    }
+
+    impl LegacyProxy {
+        /// Lays out storage the way the proxy constructor did before storage versioning.
+        /// Deployed proxies reach this code through `Contracts::set_code` instead.
+        #[ink(constructor)]
+        pub fn new(
+            rmrk_contract: AccountId,
+            catalog_contract: AccountId,
+            mint_price: Balance,
+        ) -> Self {
+            let mut instance = Self::default();
+            instance.proxy.rmrk_contract = Option::Some(rmrk_contract);
+            instance.proxy.catalog_contract = Option::Some(catalog_contract);
+            instance.proxy.salt = 0;
+            instance.proxy.mint_price = mint_price;
+
+            let caller = instance.env().caller();
+            instance._init_with_owner(caller);
+            instance
+        }
+
+        /// Writes the proxy storage in layout 1 and switches the contract to proxy code uploaded
+        /// under `code_hash`. The owner of the old proxy gets every role of the new one and is
+        /// the only one who can call it.
+        ///
+        /// Takes `&self`, so the old storage isn't written back over the converted one.
+        #[ink(message)]
+        pub fn migrate(&self, code_hash: [u8; 32]) -> Result<(), ProxyError> {
+            let owner = self.ownable.owner;
+            ensure!(
+                Self::env().caller() == owner,
+                AccessControlError::MissingRole
+            );
+            self.write_v1(owner);
+            ink::env::set_code_hash(&code_hash).map_err(|_| ProxyError::UpgradeFailed)?;
+
+            Self::env().emit_event(Migrated {
+                from: 0,
+                to: STORAGE_VERSION,
+            });
+            Ok(())
+        }
+
+        fn write_v1(&self, owner: AccountId) {
+            let proxy = RmrkProxy::from_v0(owner, &self.proxy);
+            ink::env::set_contract_storage(&<RmrkProxy as StorageKey>::KEY, &proxy);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink::env::test;
+        use openbrush::contracts::access_control::AccessControl;
+        use rmrk_proxy::{
+            ADMIN,
+            PRICE_MANAGER,
+        };
+
+        const RMRK_ADDRESS: [u8; 32] = [0x10; 32];
+        const CATALOG_ADDRESS: [u8; 32] = [0x11; 32];
+
+        #[ink::test]
+        fn write_v1_keeps_legacy_data() {
+            let contract = init();
+            let accounts = default_accounts();
+            contract.write_v1(accounts.alice);
+
+            let proxy: RmrkProxy = ink::env::get_contract_storage(&<RmrkProxy as StorageKey>::KEY)
+                .expect("Proxy storage decodes")
+                .expect("Proxy storage is written");
+            assert_eq!(proxy.storage_version(), STORAGE_VERSION);
+            assert_eq!(proxy.rmrk_contract_address(), Some(RMRK_ADDRESS.into()));
+            assert_eq!(
+                proxy.catalog_contract_address(),
+                Some(CATALOG_ADDRESS.into())
+            );
+            assert_eq!(proxy.mint_price(), 5);
+            assert!(proxy.has_role(ADMIN, accounts.alice));
+            assert!(proxy.has_role(PRICE_MANAGER, accounts.alice));
+        }
+
+        #[ink::test]
+        fn migrate_fails_if_not_owner() {
+            let contract = init();
+            test::set_caller::<ink::env::DefaultEnvironment>(default_accounts().bob);
+            assert_eq!(
+                contract.migrate([0x01; 32]),
+                Err(ProxyError::AccessControl(AccessControlError::MissingRole))
+            );
+        }
+
+        fn init() -> LegacyProxy {
+            LegacyProxy::new(RMRK_ADDRESS.into(), CATALOG_ADDRESS.into(), 5)
+        }
+
+        fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            test::default_accounts::<Environment>()
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::LegacyProxyRef;
+        use ink::primitives::AccountId;
+        use ink_e2e::build_message;
+        use openbrush::contracts::access_control::{
+            access_control_external::AccessControl,
+            AccessControlError,
+        };
+        use rmrk_proxy::{
+            proxy::rmrk_proxy::RmrkProxyRef,
+            ProxyError,
+            ADMIN,
+            STORAGE_VERSION,
+        };
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn migrate_keeps_legacy_state(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let alice = ink_e2e::alice();
+            let alice_account_id = AccountId::try_from(alice.account_id().as_ref()).unwrap();
+
+            // *************** Deploy storage laid out before versioning ***************
+            let legacy_constructor = LegacyProxyRef::new(alice_account_id, alice_account_id, 5);
+            let proxy_address = client
+                .instantiate("rmrk_proxy_migration", &alice, legacy_constructor, 0, None)
+                .await
+                .expect("Legacy proxy instantiation failed")
+                .account_id;
+            let code_hash: [u8; 32] = client
+                .upload("rmrk_proxy", &alice, None)
+                .await
+                .expect("Proxy code upload failed")
+                .code_hash
+                .as_ref()
+                .try_into()
+                .unwrap();
+
+            // *************** Migrate to the proxy code ***************
+            let migrate_message = build_message::<LegacyProxyRef>(proxy_address.clone())
+                .call(|legacy| legacy.migrate(code_hash));
+            let migrate_result = client
+                .call_dry_run(&ink_e2e::bob(), &migrate_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(
+                migrate_result,
+                Err(ProxyError::AccessControl(AccessControlError::MissingRole))
+            );
+            let migrate_result = client
+                .call(&alice, migrate_message, 0, None)
+                .await
+                .expect("Migrate failed");
+            assert_eq!(migrate_result.return_value(), Ok(()));
+
+            // *************** Check the proxy kept the legacy state ***************
+            let storage_version_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.storage_version());
+            let storage_version = client
+                .call_dry_run(&alice, &storage_version_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(storage_version, STORAGE_VERSION);
+
+            let rmrk_contract_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.rmrk_contract_address());
+            let rmrk_contract = client
+                .call_dry_run(&alice, &rmrk_contract_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(rmrk_contract, Some(alice_account_id));
+
+            let has_role_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.has_role(ADMIN, alice_account_id));
+            let has_role = client
+                .call_dry_run(&alice, &has_role_message, 0, None)
+                .await
+                .return_value();
+            assert!(has_role);
+
+            // The converted storage is written back by messages of the new code.
+            let set_price_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.set_mint_price(6));
+            client
+                .call(&alice, set_price_message, 0, None)
+                .await
+                .expect("Set mint price failed");
+            let mint_price_message = build_message::<RmrkProxyRef>(proxy_address.clone())
+                .call(|proxy| proxy.mint_price());
+            let mint_price = client
+                .call_dry_run(&alice, &mint_price_message, 0, None)
+                .await
+                .return_value();
+            assert_eq!(mint_price, 6);
+
+            Ok(())
+        }
+    }
+}